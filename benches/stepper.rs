@@ -0,0 +1,60 @@
+//!CPU 演化速度对比
+//!用法: cargo bench --bench stepper
+//!在同样的随机棋盘上分别用 Board、多线程 ParallelBoard、单线程 BitBoard 与多线程 BitBoard 演化,输出每秒演化的代数
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use conway_wgpu::{BitBoard, Board, Engine, ParallelBoard, Rule};
+
+//每种实现至少运行的时间
+const MIN_DURATION: Duration = Duration::from_millis(500);
+const SIZES: &[usize] = &[64, 256, 1024, 4096];
+
+//反复演化直到超过 MIN_DURATION,返回每秒演化的代数
+fn measure(mut step: impl FnMut()) -> f64 {
+    let start = Instant::now();
+    let mut generations = 0u64;
+    while start.elapsed() < MIN_DURATION {
+        step();
+        generations += 1;
+    }
+    generations as f64 / start.elapsed().as_secs_f64()
+}
+
+fn main() {
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    let rule = Rule::CONWAY;
+    println!(
+        "{:>10} {:>14} {:>18} {:>14} {:>18} {:>8}",
+        "尺寸",
+        "Board",
+        format!("ParallelBoard x{}", threads),
+        "BitBoard",
+        format!("BitBoard x{}", threads),
+        "加速",
+    );
+
+    for &size in SIZES {
+        let board = Board::random(size, size);
+        let mut dense = board.clone();
+        let mut parallel_dense = ParallelBoard::new(board.clone(), threads).unwrap();
+        let mut bits = BitBoard::from_board(&board);
+        let mut parallel = BitBoard::from_board(&board);
+        parallel.set_threads(threads);
+
+        let dense_speed = measure(|| dense.step(&rule));
+        let parallel_dense_speed = measure(|| parallel_dense.step(&rule));
+        let bits_speed = measure(|| bits.step(&rule));
+        let parallel_speed = measure(|| parallel.step(&rule));
+        println!(
+            "{:>10} {:>12.1}/s {:>16.1}/s {:>12.1}/s {:>16.1}/s {:>7.1}x",
+            format!("{}x{}", size, size),
+            dense_speed,
+            parallel_dense_speed,
+            bits_speed,
+            parallel_speed,
+            bits_speed.max(parallel_speed) / dense_speed,
+        );
+    }
+}
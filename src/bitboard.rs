@@ -0,0 +1,315 @@
+//!按位压缩的稠密棋盘
+//!每行细胞压缩进若干个 u64,第 x 个细胞是第 x / 64 个字的第 x % 64 位
+//!演化时把 8 个邻居按位相加,一次计算 64 个细胞,不需要逐个细胞查找邻居
+//!边界拓扑通过在棋盘四周加一圈映射过来的细胞实现,结果与 Board 完全一致
+//!可以把棋盘按行分成若干段,在多个线程上同时演化
+
+use std::thread;
+
+use crate::board::Board;
+use crate::engine::Engine;
+use crate::pattern::Pattern;
+use crate::rule::Rule;
+use crate::topology::Topology;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BitBoard {
+    width: usize,
+    height: usize,
+    //每行占用的字数
+    words: usize,
+    cells: Vec<u64>,
+    topology: Topology,
+    threads: usize,
+}
+
+impl BitBoard {
+    //创建一个全部为死细胞的棋盘
+    pub fn new(width: usize, height: usize) -> Self {
+        let words = width.div_ceil(64);
+        BitBoard {
+            width,
+            height,
+            words,
+            cells: vec![0; words * height],
+            topology: Topology::default(),
+            threads: 1,
+        }
+    }
+
+    //复制稠密棋盘,包括边界拓扑
+    pub fn from_board(board: &Board) -> Self {
+        let mut bits = BitBoard::new(board.width(), board.height()).with_topology(board.topology());
+        for (x, y) in board.live_cells() {
+            bits.set(x as usize, y as usize, true);
+        }
+        bits
+    }
+
+    pub fn to_board(&self) -> Board {
+        let mut board = Board::new(self.width, self.height).with_topology(self.topology);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self.get(x, y) {
+                    board.set(x, y, true);
+                }
+            }
+        }
+        board
+    }
+
+    //指定边界拓扑
+    pub fn with_topology(mut self, topology: Topology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    pub fn set_topology(&mut self, topology: Topology) {
+        self.topology = topology;
+    }
+
+    //演化时使用的线程数,棋盘按行平均分给各个线程
+    pub fn set_threads(&mut self, threads: usize) {
+        self.threads = threads.max(1);
+    }
+
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    //越界的坐标视为死细胞
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height && self.cells[y * self.words + x / 64] >> (x % 64) & 1 == 1
+    }
+
+    //越界的坐标会被忽略
+    pub fn set(&mut self, x: usize, y: usize, alive: bool) {
+        if x < self.width && y < self.height {
+            let word = &mut self.cells[y * self.words + x / 64];
+            if alive {
+                *word |= 1 << (x % 64);
+            } else {
+                *word &= !(1 << (x % 64));
+            }
+        }
+    }
+
+    //活细胞数量
+    pub fn population(&self) -> usize {
+        self.cells.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    //按照给定规则演化一代
+    pub fn step(&mut self, rule: &Rule) {
+        if self.width == 0 || self.height == 0 {
+            return;
+        }
+        let padded = self.padded_rows();
+        let rule = BitRule::new(rule);
+        let stride = self.words + 1;
+        let (words, width) = (self.words, self.width);
+        let band_rows = self.height.div_ceil(self.threads);
+
+        let step_band = |band: usize, cells: &mut [u64]| {
+            for (row, output) in cells.chunks_mut(words).enumerate() {
+                let y = band * band_rows + row;
+                //填充后的第 y、y + 1、y + 2 行分别是原来的第 y - 1、y、y + 1 行
+                let rows = [0, 1, 2].map(|i| &padded[(y + i) * stride..(y + i + 1) * stride]);
+                step_row(&rows, output, width, &rule);
+            }
+        };
+        if self.threads == 1 {
+            step_band(0, &mut self.cells);
+        } else {
+            thread::scope(|scope| {
+                for (band, cells) in self.cells.chunks_mut(band_rows * words).enumerate() {
+                    let step_band = &step_band;
+                    scope.spawn(move || step_band(band, cells));
+                }
+            });
+        }
+    }
+
+    //四周各加一圈细胞后的棋盘,共 height + 2 行,每行 words + 1 个字
+    //第 x 个细胞位于第 x + 1 位,第 0 位与第 width + 1 位是按拓扑映射过来的左右邻居
+    fn padded_rows(&self) -> Vec<u64> {
+        let stride = self.words + 1;
+        let mut padded = vec![0u64; stride * (self.height + 2)];
+        let (width, height) = (self.width as isize, self.height as isize);
+
+        for (i, row) in padded.chunks_mut(stride).enumerate() {
+            let y = i as isize - 1;
+            //越过上下边缘时整行映射到另一行,克莱因瓶与交叉帽还需要左右翻转
+            let source = match self.topology {
+                _ if (0..height).contains(&y) => Some((y as usize, false)),
+                Topology::Bounded => None,
+                Topology::Torus => Some((y.rem_euclid(height) as usize, false)),
+                Topology::KleinBottle | Topology::CrossSurface => Some((y.rem_euclid(height) as usize, true)),
+            };
+            match source {
+                Some((source, false)) => {
+                    let words = &self.cells[source * self.words..(source + 1) * self.words];
+                    for (j, &word) in words.iter().enumerate() {
+                        row[j] |= word << 1;
+                        row[j + 1] |= word >> 63;
+                    }
+                }
+                Some((source, true)) => {
+                    for x in 0..self.width {
+                        if self.get(self.width - 1 - x, source) {
+                            row[(x + 1) / 64] |= 1 << ((x + 1) % 64);
+                        }
+                    }
+                }
+                None => {}
+            }
+            for x in [-1, width] {
+                let neighbor = self.topology.wrap(x, y, self.width, self.height);
+                if neighbor.is_some_and(|(nx, ny)| self.get(nx, ny)) {
+                    let bit = (x + 1) as usize;
+                    row[bit / 64] |= 1 << (bit % 64);
+                }
+            }
+        }
+        padded
+    }
+}
+
+//规则展开为邻居数量与对应的出生/存活条件,跳过既不出生也不存活的数量
+struct BitRule {
+    counts: Vec<(usize, bool, bool)>,
+}
+
+impl BitRule {
+    fn new(rule: &Rule) -> Self {
+        let counts = (0..=8)
+            .map(|n| (n, rule.born(n), rule.survives(n)))
+            .filter(|&(_, born, survives)| born || survives)
+            .collect();
+        BitRule { counts }
+    }
+}
+
+//由填充后的相邻三行计算一行的下一代
+fn step_row(rows: &[&[u64]; 3], output: &mut [u64], width: usize, rule: &BitRule) {
+    let last = output.len() - 1;
+    for (i, word) in output.iter_mut().enumerate() {
+        //相对于第 i 个字右移 shift 位,shift 为 0、1、2 时分别得到左邻居、本身、右邻居
+        let shifted = |row: &[u64], shift: u32| match shift {
+            0 => row[i],
+            _ => row[i] >> shift | row[i + 1] << (64 - shift),
+        };
+        let [below, middle, above] = *rows;
+        let neighbors = [
+            shifted(below, 0),
+            shifted(below, 1),
+            shifted(below, 2),
+            shifted(middle, 0),
+            shifted(middle, 2),
+            shifted(above, 0),
+            shifted(above, 1),
+            shifted(above, 2),
+        ];
+        let alive = shifted(middle, 1);
+
+        //按位相加,sum[k] 的每一位是对应细胞邻居数量的第 k 位
+        let mut sum = [0u64; 4];
+        for neighbor in neighbors {
+            let mut carry = neighbor;
+            for bit in sum.iter_mut() {
+                let next = *bit & carry;
+                *bit ^= carry;
+                carry = next;
+            }
+        }
+
+        let mut next = 0;
+        for &(count, born, survives) in &rule.counts {
+            let equal = sum
+                .iter()
+                .enumerate()
+                .fold(!0, |mask, (k, &bit)| mask & if count >> k & 1 == 1 { bit } else { !bit });
+            let state = match (born, survives) {
+                (true, true) => !0,
+                (true, false) => !alive,
+                (false, true) => alive,
+                (false, false) => 0,
+            };
+            next |= equal & state;
+        }
+        //最后一个字中超出棋盘宽度的位保持为 0
+        if i == last && !width.is_multiple_of(64) {
+            next &= (1 << (width % 64)) - 1;
+        }
+        *word = next;
+    }
+}
+
+impl Engine for BitBoard {
+    fn step(&mut self, rule: &Rule) {
+        BitBoard::step(self, rule);
+    }
+
+    fn get(&self, x: i64, y: i64) -> bool {
+        x >= 0 && y >= 0 && BitBoard::get(self, x as usize, y as usize)
+    }
+
+    fn set(&mut self, x: i64, y: i64, alive: bool) {
+        if x >= 0 && y >= 0 {
+            BitBoard::set(self, x as usize, y as usize, alive);
+        }
+    }
+
+    fn population(&self) -> usize {
+        BitBoard::population(self)
+    }
+
+    fn live_cells(&self) -> Vec<(i64, i64)> {
+        let mut cells = Vec::new();
+        for (i, &word) in self.cells.iter().enumerate() {
+            let (y, base) = (i / self.words, i % self.words * 64);
+            let mut bits = word;
+            while bits != 0 {
+                cells.push(((base + bits.trailing_zeros() as usize) as i64, y as i64));
+                bits &= bits - 1;
+            }
+        }
+        cells
+    }
+
+    fn set_topology(&mut self, topology: Topology) {
+        BitBoard::set_topology(self, topology);
+    }
+
+    //保留整个棋盘的大小
+    fn to_pattern(&self) -> Pattern {
+        Pattern::from_board(&self.to_board())
+    }
+
+    //直接从各个字中取出活细胞,不经过 Board
+    fn write_texels(&self, texels: &mut Vec<u8>) -> bool {
+        texels.clear();
+        texels.resize(self.width * self.height, 0);
+        for (i, &word) in self.cells.iter().enumerate() {
+            let (y, base) = (i / self.words, i % self.words * 64);
+            let mut bits = word;
+            while bits != 0 {
+                texels[y * self.width + base + bits.trailing_zeros() as usize] = u8::MAX;
+                bits &= bits - 1;
+            }
+        }
+        true
+    }
+}
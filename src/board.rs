@@ -0,0 +1,150 @@
+//!棋盘与演化
+//!棋盘按行存储,(x, y) 对应 cells[y * width + x],y 轴向上与渲染保持一致
+//!par_step 把棋盘按行分段在多个线程上演化,适合 4096x4096 以上的大棋盘
+
+use rayon::prelude::*;
+
+use crate::rule::Rule;
+use crate::topology::Topology;
+
+// 并行演化时每个线程平均分到的行段数,分得更细可以让各线程的负载更均衡
+const BANDS_PER_THREAD: usize = 4;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Board {
+    width: usize,
+    height: usize,
+    cells: Vec<bool>,
+    topology: Topology,
+}
+
+impl Board {
+    //创建一个全部为死细胞的棋盘
+    pub fn new(width: usize, height: usize) -> Self {
+        Board {
+            width,
+            height,
+            cells: vec![false; width * height],
+            topology: Topology::default(),
+        }
+    }
+
+    //指定边界拓扑
+    pub fn with_topology(mut self, topology: Topology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    //创建一个随机填充的棋盘
+    pub fn random(width: usize, height: usize) -> Self {
+        let mut board = Board::new(width, height);
+        for cell in board.cells.iter_mut() {
+            *cell = rand::random::<bool>(); // 随机填充
+        }
+        board
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    pub fn set_topology(&mut self, topology: Topology) {
+        self.topology = topology;
+    }
+
+    //越界的坐标视为死细胞
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height && self.cells[y * self.width + x]
+    }
+
+    //越界的坐标会被忽略
+    pub fn set(&mut self, x: usize, y: usize, alive: bool) {
+        if x < self.width && y < self.height {
+            self.cells[y * self.width + x] = alive;
+        }
+    }
+
+    //清空棋盘
+    pub fn clear(&mut self) {
+        self.cells.iter_mut().for_each(|cell| *cell = false);
+    }
+
+    //活细胞数量
+    pub fn population(&self) -> usize {
+        self.cells.iter().filter(|&&cell| cell).count()
+    }
+
+    //包含所有活细胞的最小矩形 (最小 x, 最小 y, 最大 x, 最大 y),没有活细胞时为 None
+    pub fn bounding_box(&self) -> Option<(usize, usize, usize, usize)> {
+        let mut bounds: Option<(usize, usize, usize, usize)> = None;
+        for (i, _) in self.cells.iter().enumerate().filter(|(_, &cell)| cell) {
+            let (x, y) = (i % self.width, i / self.width);
+            bounds = Some(match bounds {
+                Some((min_x, min_y, max_x, max_y)) => (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y)),
+                None => (x, y, x, y),
+            });
+        }
+        bounds
+    }
+
+    //按行存储的细胞数据
+    pub fn cells(&self) -> &[bool] {
+        &self.cells
+    }
+
+    // 计算一个细胞周围活细胞的数量,越过边缘的邻居按拓扑结构映射
+    pub fn count_neighbors(&self, x: usize, y: usize) -> usize {
+        let mut count = 0;
+        for i in -1..=1 {
+            for j in -1..=1 {
+                if i == 0 && j == 0 {
+                    continue;
+                }
+                let neighbor = self.topology.wrap(x as isize + i, y as isize + j, self.width, self.height);
+                if neighbor.is_some_and(|(nx, ny)| self.get(nx, ny)) {
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+
+    // 按照给定规则更新棋盘的状态,演化一代
+    pub fn step(&mut self, rule: &Rule) {
+        let mut new_cells = vec![false; self.cells.len()];
+        self.step_rows(rule, 0, &mut new_cells);
+        self.cells = new_cells;
+    }
+
+    // 与 step 相同,但把棋盘按行分段后在 rayon 当前的线程池中并行计算,结果与 step 完全一致
+    pub fn par_step(&mut self, rule: &Rule) {
+        if self.cells.is_empty() {
+            return;
+        }
+        let bands = rayon::current_num_threads() * BANDS_PER_THREAD;
+        let band_rows = self.height.div_ceil(bands);
+        let mut new_cells = vec![false; self.cells.len()];
+        new_cells
+            .par_chunks_mut(band_rows * self.width)
+            .enumerate()
+            .for_each(|(band, output)| self.step_rows(rule, band * band_rows, output));
+        self.cells = new_cells;
+    }
+
+    // 从第 first_row 行开始计算若干整行的下一代,写入 output
+    fn step_rows(&self, rule: &Rule, first_row: usize, output: &mut [bool]) {
+        for (i, cell) in output.iter_mut().enumerate() {
+            let (x, y) = (i % self.width, first_row + i / self.width);
+            let neighbors = self.count_neighbors(x, y);
+            *cell = rule.next_state(self.get(x, y), neighbors);
+        }
+    }
+}
@@ -0,0 +1,76 @@
+//!相机 uniform 缓冲区
+//!保存 View 给出的视图投影矩阵及其逆矩阵,作为所有渲染管线的第 0 个绑定组
+
+use wgpu::*;
+use wgpu::util::DeviceExt;
+
+use crate::view::View;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct CameraUniform {
+    //棋盘坐标到裁剪空间
+    view_proj: [[f32; 4]; 4],
+    //裁剪空间到棋盘坐标
+    inverse_view_proj: [[f32; 4]; 4],
+}
+
+impl CameraUniform {
+    fn new(view: &View) -> Self {
+        CameraUniform {
+            view_proj: view.view_proj().into(),
+            inverse_view_proj: view.inverse_view_proj().into(),
+        }
+    }
+}
+
+pub struct Camera {
+    buffer: Buffer,
+    bind_group_layout: BindGroupLayout,
+    bind_group: BindGroup,
+}
+
+impl Camera {
+    pub fn new(device: &Device, view: &View) -> Self {
+        let buffer = device.create_buffer_init(&util::BufferInitDescriptor {
+            label: Some("Camera Buffer"),
+            contents: bytemuck::bytes_of(&CameraUniform::new(view)),
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("Camera Bind Group Layout"),
+            entries: &[BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStages::VERTEX | ShaderStages::FRAGMENT,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: BufferSize::new(std::mem::size_of::<CameraUniform>() as _),
+                },
+                count: None,
+            }],
+        });
+
+        let bind_group = device.create_bind_group(&BindGroupDescriptor {
+            label: Some("Camera Bind Group"),
+            layout: &bind_group_layout,
+            entries: &[BindGroupEntry { binding: 0, resource: buffer.as_entire_binding() }],
+        });
+
+        Camera { buffer, bind_group_layout, bind_group }
+    }
+
+    //把 view 的当前状态写入 uniform 缓冲区
+    pub fn update(&self, queue: &Queue, view: &View) {
+        queue.write_buffer(&self.buffer, 0, bytemuck::bytes_of(&CameraUniform::new(view)));
+    }
+
+    pub fn bind_group_layout(&self) -> &BindGroupLayout {
+        &self.bind_group_layout
+    }
+
+    pub fn bind_group(&self) -> &BindGroup {
+        &self.bind_group
+    }
+}
@@ -0,0 +1,446 @@
+//!命令行参数解析
+//!用法: ConwayWgpu [run] [选项]
+
+use std::fmt;
+use std::path::PathBuf;
+
+use conway_wgpu::hashlife::MAX_STEP_LOG2;
+use conway_wgpu::paint::MAX_BRUSH_SIZE;
+use conway_wgpu::raster::{self, ColorScheme};
+use conway_wgpu::record::{self, RecordOptions};
+use conway_wgpu::stats::StatsFormat;
+use conway_wgpu::{pattern, BitBoard, Board, Engine, HashLife, ParallelBoard, Rule, SparseUniverse, Topology};
+
+pub const USAGE: &str = "\
+用法: ConwayWgpu [选项]            打开窗口运行
+      ConwayWgpu run [选项]        不打开窗口,演化指定代数后输出结果与统计
+
+选项:
+    -s, --size <宽x高>     棋盘尺寸,例如 256x144 (默认 20x20,载入图样时默认为图样大小)
+        --width <宽>       棋盘宽度
+        --height <高>      棋盘高度
+    -r, --rule <规则>      演化规则,支持 B3/S23 与 23/3 记法 (默认 B3/S23)
+    -t, --topology <拓扑>  边界拓扑: bounded、torus、klein、cross (默认 bounded)
+    -e, --engine <引擎>    CPU 模拟引擎: dense (固定大小棋盘)、parallel (多线程按行分段演化的 dense)、
+                           bitboard (按位压缩的固定大小棋盘)、
+                           sparse (只保存活细胞的无限宇宙)、
+                           hashlife (四叉树与结果缓存的无限宇宙,适合长时间演化) (默认 dense)
+        --threads <N>      parallel 与 bitboard 按行分段并行演化的线程数
+                           (parallel 默认为 CPU 核心数,bitboard 默认 1)
+        --memory <MB>      hashlife 节点缓存的内存上限,超过时回收不再使用的节点 (默认 1024)
+        --step <N>         无限宇宙每次演化 2^N 代,窗口中按 PageUp/PageDown 调整 (默认 0)
+    -i, --input <文件>     载入图样文件,放在棋盘中央 (默认随机填充)
+        --gpu              使用计算着色器在 GPU 上演化
+        --brush <边长>     鼠标绘制时方形笔刷的边长 (默认 1,最大 64)
+        --renderer <方式>  绘制方式: instanced (每个活细胞一个实例)、grid (纹理全屏绘制) (默认 instanced)
+        --record <文件>    录制动画的文件名,支持 .gif、.png 与 .apng
+                           (窗口中按 V 键开始/停止录制,默认保存为 conway-<代数>.gif)
+        --record-delay <毫秒>  动画每帧显示的时间 (默认 100)
+    -h, --help             显示帮助
+
+run 选项:
+    -g, --gens <代数>      演化的代数 (默认 1000)
+    -o, --output <文件>    保存演化结果
+        --snapshot <文件>  把演化结果画成 PNG 图片,在 CPU 上绘制,不需要 wgpu
+        --snapshot-gpu     截图改用 wgpu 离屏渲染 (优先使用软件适配器,只支持默认配色)
+        --stats <文件>     保存每一代的种群、出生、死亡与包围盒,支持 .csv 与 .json
+        --period           逐代检测棋盘是否进入循环,输出最后一代的周期与位移 (静物、振荡子或飞船)
+                           (窗口中同样有效,也可以按 C 键开启/关闭)
+        --record-range <起始..结束>  录制的代数范围,不包含结束代 (默认录制所有代,未指定 --gens 时演化到范围末尾)
+        --record-scale <N>     录制时每个细胞的边长(像素) (默认 4,最大 64)
+        --colors <活,死[,网格线]>  截图与录制时的颜色,例如 ffffff,000000,898989 (默认白色、黑色与灰色)
+                           (也可以写作 --record-colors)
+        --grid-lines       截图与录制时在细胞边缘画网格线 (细胞边长大于 4 像素时)";
+
+//默认棋盘边长
+const DEFAULT_SIZE: usize = 20;
+//录制时每个细胞边长的上限
+const MAX_RECORD_SCALE: u32 = 64;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Command {
+    //打开窗口
+    #[default]
+    Window,
+    //无窗口批量演化
+    Run,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EngineKind {
+    //固定大小的稠密棋盘,支持各种边界拓扑
+    #[default]
+    Dense,
+    //在线程池中按行分段演化的稠密棋盘
+    Parallel,
+    //按位压缩的稠密棋盘
+    BitBoard,
+    //只保存活细胞的无限宇宙
+    Sparse,
+    //四叉树与结果缓存的无限宇宙
+    HashLife,
+}
+
+impl EngineKind {
+    //是否没有边界
+    pub fn is_unbounded(self) -> bool {
+        matches!(self, EngineKind::Sparse | EngineKind::HashLife)
+    }
+}
+
+impl fmt::Display for EngineKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            EngineKind::Dense => "dense",
+            EngineKind::Parallel => "parallel",
+            EngineKind::BitBoard => "bitboard",
+            EngineKind::Sparse => "sparse",
+            EngineKind::HashLife => "hashlife",
+        })
+    }
+}
+
+//初始状态,有边界的引擎使用稠密棋盘,无限宇宙只保存活细胞,不分配稠密棋盘
+#[derive(Clone, Debug)]
+pub enum Initial {
+    Board(Board),
+    //活细胞坐标 y 轴向上,与图样放在 width x height 棋盘中央时一致
+    Cells { width: usize, height: usize, cells: Vec<(i64, i64)> },
+}
+
+impl Initial {
+    //初始棋盘或图样所在区域的尺寸
+    pub fn size(&self) -> (usize, usize) {
+        match self {
+            Initial::Board(board) => (board.width(), board.height()),
+            Initial::Cells { width, height, .. } => (*width, *height),
+        }
+    }
+
+    //转换为稠密棋盘,超出棋盘的细胞被裁掉
+    pub fn into_board(self) -> Result<Board, String> {
+        match self {
+            Initial::Board(board) => Ok(board),
+            Initial::Cells { width, height, cells } => {
+                pattern::check_board_size(width, height).map_err(|e| e.to_string())?;
+                let mut board = Board::new(width, height);
+                for (x, y) in cells {
+                    Engine::set(&mut board, x, y, true);
+                }
+                Ok(board)
+            }
+        }
+    }
+
+    pub fn into_cells(self) -> Vec<(i64, i64)> {
+        match self {
+            Initial::Board(board) => Engine::live_cells(&board),
+            Initial::Cells { cells, .. } => cells,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Renderer {
+    //每个活细胞一个实例
+    #[default]
+    Instanced,
+    //棋盘作为纹理,用全屏三角形绘制
+    Grid,
+}
+
+#[derive(Default)]
+pub struct Options {
+    pub command: Command,
+    pub width: Option<usize>,
+    pub height: Option<usize>,
+    //未指定时使用图样文件中的规则
+    pub rule: Option<Rule>,
+    pub topology: Topology,
+    pub engine: EngineKind,
+    pub input: Option<PathBuf>,
+    pub output: Option<PathBuf>,
+    pub snapshot: Option<PathBuf>,
+    //截图使用 wgpu 离屏渲染而不是 CPU 光栅化
+    pub snapshot_gpu: bool,
+    pub record: Option<PathBuf>,
+    pub record_options: RecordOptions,
+    //录制 [起始, 结束) 代
+    pub record_range: Option<(u64, u64)>,
+    pub colors: ColorScheme,
+    pub grid_lines: bool,
+    pub period: bool,
+    pub stats: Option<PathBuf>,
+    pub generations: Option<u64>,
+    pub gpu: bool,
+    pub renderer: Renderer,
+    pub brush: Option<usize>,
+    //hashlife 的内存上限,单位为 MB
+    pub memory: Option<usize>,
+    //无限宇宙每次演化 2^step 代
+    pub step: Option<u32>,
+    //parallel 与 bitboard 演化时的线程数
+    pub threads: Option<usize>,
+    pub help: bool,
+}
+
+impl Options {
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Options, String> {
+        let mut options = Options::default();
+        let mut args = args.into_iter().peekable();
+        if args.peek().map(String::as_str) == Some("run") {
+            args.next();
+            options.command = Command::Run;
+        }
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-s" | "--size" => {
+                    let value = next_value(&mut args, &arg)?;
+                    let (width, height) = value
+                        .split_once(['x', 'X'])
+                        .ok_or_else(|| format!("无效的棋盘尺寸 `{}`,应为 宽x高", value))?;
+                    options.width = Some(parse_dimension(width)?);
+                    options.height = Some(parse_dimension(height)?);
+                }
+                "--width" => options.width = Some(parse_dimension(&next_value(&mut args, &arg)?)?),
+                "--height" => options.height = Some(parse_dimension(&next_value(&mut args, &arg)?)?),
+                "-r" | "--rule" => {
+                    let value = next_value(&mut args, &arg)?;
+                    let rule = value
+                        .parse()
+                        .map_err(|e| format!("无效的规则 `{}`: {}", value, e))?;
+                    options.rule = Some(rule);
+                }
+                "-t" | "--topology" => options.topology = next_value(&mut args, &arg)?.parse()?,
+                "-e" | "--engine" => {
+                    options.engine = match next_value(&mut args, &arg)?.as_str() {
+                        "dense" => EngineKind::Dense,
+                        "parallel" => EngineKind::Parallel,
+                        "bitboard" => EngineKind::BitBoard,
+                        "sparse" => EngineKind::Sparse,
+                        "hashlife" => EngineKind::HashLife,
+                        other => return Err(format!("未知的引擎 `{}`,可选 dense、parallel、bitboard、sparse、hashlife", other)),
+                    }
+                }
+                "-i" | "--input" => options.input = Some(next_value(&mut args, &arg)?.into()),
+                "-o" | "--output" => options.output = Some(next_value(&mut args, &arg)?.into()),
+                "--snapshot" => options.snapshot = Some(next_value(&mut args, &arg)?.into()),
+                "--snapshot-gpu" => options.snapshot_gpu = true,
+                "--record" => {
+                    let path: PathBuf = next_value(&mut args, &arg)?.into();
+                    if record::RecordFormat::from_path(&path).is_none() {
+                        return Err(format!("无法识别动画文件 `{}` 的格式,可选 .gif、.png、.apng", path.display()));
+                    }
+                    options.record = Some(path);
+                }
+                "--record-delay" => {
+                    let value = next_value(&mut args, &arg)?;
+                    options.record_options.delay_ms = match value.trim().parse::<u16>() {
+                        Ok(n) if n > 0 => n,
+                        _ => return Err(format!("无效的帧间隔 `{}`,应为 1 到 65535 的整数 (毫秒)", value)),
+                    };
+                }
+                "--record-scale" => {
+                    let value = next_value(&mut args, &arg)?;
+                    options.record_options.raster.cell_size = match value.trim().parse::<u32>() {
+                        Ok(n) if (1..=MAX_RECORD_SCALE).contains(&n) => n,
+                        _ => return Err(format!("无效的缩放倍数 `{}`,应为 1 到 {} 的整数", value, MAX_RECORD_SCALE)),
+                    };
+                }
+                //--record-colors 是早期版本的名称,保留为别名
+                "--colors" | "--record-colors" => {
+                    let value = next_value(&mut args, &arg)?;
+                    let colors = value.split(',').map(raster::parse_color).collect::<Option<Vec<_>>>();
+                    options.colors = match colors.as_deref() {
+                        Some(&[alive, dead]) => ColorScheme { alive, dead, ..ColorScheme::default() },
+                        Some(&[alive, dead, grid]) => ColorScheme { alive, dead, grid },
+                        _ => {
+                            return Err(format!(
+                                "无效的颜色 `{}`,应为 活细胞,死细胞[,网格线],例如 ffffff,000000,898989",
+                                value
+                            ))
+                        }
+                    };
+                }
+                "--grid-lines" => options.grid_lines = true,
+                "--period" => options.period = true,
+                "--stats" => {
+                    let path: PathBuf = next_value(&mut args, &arg)?.into();
+                    if StatsFormat::from_path(&path).is_none() {
+                        return Err(format!("无法识别统计文件 `{}` 的格式,可选 .csv、.json", path.display()));
+                    }
+                    options.stats = Some(path);
+                }
+                "--record-range" => {
+                    let value = next_value(&mut args, &arg)?;
+                    let range = value
+                        .split_once("..")
+                        .and_then(|(start, end)| Some((start.trim().parse::<u64>().ok()?, end.trim().parse::<u64>().ok()?)))
+                        .filter(|(start, end)| start < end);
+                    options.record_range =
+                        Some(range.ok_or_else(|| format!("无效的代数范围 `{}`,应为 起始..结束,例如 0..100", value))?);
+                }
+                "-g" | "--gens" => {
+                    let value = next_value(&mut args, &arg)?;
+                    let generations = value
+                        .trim()
+                        .parse()
+                        .map_err(|_| format!("无效的代数 `{}`,应为非负整数", value))?;
+                    options.generations = Some(generations);
+                }
+                "--gpu" => options.gpu = true,
+                "--brush" => {
+                    let value = next_value(&mut args, &arg)?;
+                    let brush = match value.trim().parse::<usize>() {
+                        Ok(n) if (1..=MAX_BRUSH_SIZE).contains(&n) => n,
+                        _ => return Err(format!("无效的笔刷边长 `{}`,应为 1 到 {} 的整数", value, MAX_BRUSH_SIZE)),
+                    };
+                    options.brush = Some(brush);
+                }
+                "--memory" => {
+                    let value = next_value(&mut args, &arg)?;
+                    let memory = match value.trim().parse::<usize>() {
+                        Ok(n) if n > 0 => n,
+                        _ => return Err(format!("无效的内存上限 `{}`,应为正整数 (MB)", value)),
+                    };
+                    options.memory = Some(memory);
+                }
+                "--step" => {
+                    let value = next_value(&mut args, &arg)?;
+                    let step = match value.trim().parse::<u32>() {
+                        Ok(n) if n <= MAX_STEP_LOG2 => n,
+                        _ => return Err(format!("无效的步长指数 `{}`,应为 0 到 {} 的整数", value, MAX_STEP_LOG2)),
+                    };
+                    options.step = Some(step);
+                }
+                "--threads" => {
+                    let value = next_value(&mut args, &arg)?;
+                    let threads = match value.trim().parse::<usize>() {
+                        Ok(n) if n > 0 => n,
+                        _ => return Err(format!("无效的线程数 `{}`,应为正整数", value)),
+                    };
+                    options.threads = Some(threads);
+                }
+                "--renderer" => {
+                    options.renderer = match next_value(&mut args, &arg)?.as_str() {
+                        "instanced" => Renderer::Instanced,
+                        "grid" => Renderer::Grid,
+                        other => return Err(format!("未知的绘制方式 `{}`,可选 instanced、grid", other)),
+                    }
+                }
+                "-h" | "--help" => options.help = true,
+                _ => return Err(format!("未知参数 `{}`", arg)),
+            }
+        }
+        if options.engine != EngineKind::Dense && options.gpu {
+            return Err(format!("{} 引擎只在 CPU 上运行,不能与 --gpu 同时使用", options.engine));
+        }
+        if options.memory.is_some() && options.engine != EngineKind::HashLife {
+            return Err("--memory 只能用于 hashlife 引擎".to_string());
+        }
+        if options.threads.is_some() && !matches!(options.engine, EngineKind::Parallel | EngineKind::BitBoard) {
+            return Err("--threads 只能用于 parallel 与 bitboard 引擎".to_string());
+        }
+        if options.step.is_some() && !options.engine.is_unbounded() {
+            return Err("--step 只能用于 sparse 与 hashlife 引擎".to_string());
+        }
+        //窗口中录制的是屏幕上的画面,不由棋盘生成,这些选项只用于 run 模式
+        if options.command == Command::Window {
+            let raster_flags = [
+                ("--record-range", options.record_range.is_some()),
+                ("--record-scale", options.record_options.raster.cell_size != RecordOptions::default().raster.cell_size),
+                ("--colors", options.colors != ColorScheme::default()),
+                ("--grid-lines", options.grid_lines),
+            ];
+            if let Some((flag, _)) = raster_flags.iter().find(|(_, used)| *used) {
+                return Err(format!("{} 只能用于 run 模式,窗口中录制的是屏幕上的画面", flag));
+            }
+        }
+        if options.snapshot_gpu && options.snapshot.is_none() {
+            return Err("--snapshot-gpu 需要与 --snapshot 一起使用".to_string());
+        }
+        if options.snapshot_gpu && options.colors != ColorScheme::default() {
+            return Err("--snapshot-gpu 只支持默认配色,不能与 --colors 同时使用".to_string());
+        }
+        Ok(options)
+    }
+
+    //由初始状态创建引擎,无限宇宙中的坐标与棋盘一致,直接由活细胞生成
+    pub fn create_engine(&self, initial: Initial) -> Result<Box<dyn Engine>, String> {
+        Ok(match self.engine {
+            EngineKind::Dense => Box::new(initial.into_board()?.with_topology(self.topology)),
+            EngineKind::Parallel => {
+                let board = initial.into_board()?.with_topology(self.topology);
+                let parallel = ParallelBoard::new(board, self.threads.unwrap_or(0))
+                    .map_err(|e| format!("无法创建线程池: {}", e))?;
+                Box::new(parallel)
+            }
+            EngineKind::BitBoard => {
+                let mut bits = BitBoard::from_board(&initial.into_board()?.with_topology(self.topology));
+                if let Some(threads) = self.threads {
+                    bits.set_threads(threads);
+                }
+                Box::new(bits)
+            }
+            EngineKind::Sparse => Box::new(SparseUniverse::from_cells(initial.into_cells())),
+            EngineKind::HashLife => {
+                let mut hashlife = HashLife::from_cells(initial.into_cells());
+                if let Some(memory) = self.memory {
+                    hashlife.set_memory_limit(memory << 20);
+                }
+                Box::new(hashlife)
+            }
+        })
+    }
+
+    //生成初始状态与规则,命令行指定的规则优先于图样文件中的规则
+    //有边界的引擎把图样放进稠密棋盘,无限宇宙只取图样的活细胞,包围盒很大的稀疏图样也能载入
+    pub fn initial(&self) -> Result<(Initial, Rule), String> {
+        let (initial, pattern_rule) = match &self.input {
+            Some(path) => {
+                let pattern = pattern::load(path).map_err(|e| format!("无法载入 `{}`: {}", path.display(), e))?;
+                let (width, height) =
+                    (self.width.unwrap_or(pattern.width.max(1)), self.height.unwrap_or(pattern.height.max(1)));
+                let initial = match self.engine.is_unbounded() {
+                    true => Initial::Cells {
+                        width,
+                        height,
+                        cells: pattern.centered_cells(width, height).collect(),
+                    },
+                    false => {
+                        //先检查尺寸,避免分配失败直接中止程序
+                        pattern::check_board_size(width, height)
+                            .map_err(|e| format!("无法载入 `{}`: {}", path.display(), e))?;
+                        let mut board = Board::new(width, height);
+                        pattern.place(&mut board);
+                        Initial::Board(board.with_topology(self.topology))
+                    }
+                };
+                (initial, pattern.rule)
+            }
+            None => {
+                let (width, height) = (self.width.unwrap_or(DEFAULT_SIZE), self.height.unwrap_or(DEFAULT_SIZE));
+                pattern::check_board_size(width, height).map_err(|e| e.to_string())?;
+                let board = Board::random(width, height).with_topology(self.topology);
+                (Initial::Board(board), None)
+            }
+        };
+        let rule = self.rule.or(pattern_rule).unwrap_or_default();
+        if self.engine.is_unbounded() && rule.born(0) {
+            return Err(format!("{} 引擎不支持 B0 规则 `{}`", self.engine, rule));
+        }
+        Ok((initial, rule))
+    }
+}
+
+fn next_value<I: Iterator<Item = String>>(args: &mut I, flag: &str) -> Result<String, String> {
+    args.next().ok_or_else(|| format!("参数 `{}` 缺少取值", flag))
+}
+
+fn parse_dimension(value: &str) -> Result<usize, String> {
+    match value.trim().parse::<usize>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(format!("无效的棋盘边长 `{}`,应为正整数", value)),
+    }
+}
@@ -0,0 +1,117 @@
+//!模拟引擎的公共接口
+//!Board 是固定大小的稠密棋盘,SparseUniverse 只保存活细胞,可以在 i64 坐标上无限延伸
+//!BitBoard 与 Board 等价,按位压缩后一次演化 64 个细胞;ParallelBoard 用多个线程演化 Board
+//!HashLife 同样没有边界,用四叉树与结果缓存一次跳过 2 的幂代
+//!坐标的 y 轴向上,与 Board 一致
+
+use crate::board::Board;
+use crate::pattern::Pattern;
+use crate::rule::Rule;
+use crate::topology::Topology;
+
+pub trait Engine {
+    //演化一代
+    fn step(&mut self, rule: &Rule);
+
+    //演化多代,默认逐代调用 step
+    fn advance(&mut self, rule: &Rule, generations: u64) {
+        for _ in 0..generations {
+            self.step(rule);
+        }
+    }
+
+    //超出引擎范围的坐标视为死细胞
+    fn get(&self, x: i64, y: i64) -> bool;
+
+    //超出引擎范围的坐标被忽略
+    fn set(&mut self, x: i64, y: i64, alive: bool);
+
+    //活细胞数量
+    fn population(&self) -> usize;
+
+    //所有活细胞,顺序不确定
+    fn live_cells(&self) -> Vec<(i64, i64)>;
+
+    //min 到 max(包含两端)范围内的活细胞
+    fn live_cells_in(&self, min: (i64, i64), max: (i64, i64)) -> Vec<(i64, i64)> {
+        self.live_cells()
+            .into_iter()
+            .filter(|&(x, y)| (min.0..=max.0).contains(&x) && (min.1..=max.1).contains(&y))
+            .collect()
+    }
+
+    //包含所有活细胞的最小矩形 (最小 x, 最小 y, 最大 x, 最大 y),没有活细胞时为 None
+    fn bounding_box(&self) -> Option<(i64, i64, i64, i64)> {
+        self.live_cells().into_iter().fold(None, |bounds, (x, y)| {
+            Some(match bounds {
+                Some((min_x, min_y, max_x, max_y)) => (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y)),
+                None => (x, y, x, y),
+            })
+        })
+    }
+
+    //修改边界拓扑,没有边界的引擎忽略
+    fn set_topology(&mut self, _topology: Topology) {}
+
+    //有边界的引擎把每个细胞写成一个字节(活细胞为 255),按行从下到上排列,用于整块上传纹理
+    //texels 可以重复使用,没有边界的引擎不写入并返回 false
+    fn write_texels(&self, _texels: &mut Vec<u8>) -> bool {
+        false
+    }
+
+    //转换为图样,只包含活细胞的包围盒
+    fn to_pattern(&self) -> Pattern {
+        //图样的 y 轴向下
+        let coordinates: Vec<_> = self.live_cells().into_iter().map(|(x, y)| (x, -y)).collect();
+        Pattern::from_coordinates(&coordinates)
+    }
+}
+
+impl Engine for Board {
+    fn step(&mut self, rule: &Rule) {
+        Board::step(self, rule);
+    }
+
+    fn get(&self, x: i64, y: i64) -> bool {
+        x >= 0 && y >= 0 && Board::get(self, x as usize, y as usize)
+    }
+
+    fn set(&mut self, x: i64, y: i64, alive: bool) {
+        if x >= 0 && y >= 0 {
+            Board::set(self, x as usize, y as usize, alive);
+        }
+    }
+
+    fn population(&self) -> usize {
+        Board::population(self)
+    }
+
+    fn live_cells(&self) -> Vec<(i64, i64)> {
+        let width = self.width();
+        self.cells()
+            .iter()
+            .enumerate()
+            .filter(|(_, &alive)| alive)
+            .map(|(i, _)| ((i % width) as i64, (i / width) as i64))
+            .collect()
+    }
+
+    fn bounding_box(&self) -> Option<(i64, i64, i64, i64)> {
+        Board::bounding_box(self).map(|(min_x, min_y, max_x, max_y)| (min_x as i64, min_y as i64, max_x as i64, max_y as i64))
+    }
+
+    fn set_topology(&mut self, topology: Topology) {
+        Board::set_topology(self, topology);
+    }
+
+    //保留整个棋盘的大小
+    fn to_pattern(&self) -> Pattern {
+        Pattern::from_board(self)
+    }
+
+    fn write_texels(&self, texels: &mut Vec<u8>) -> bool {
+        texels.clear();
+        texels.extend(self.cells().iter().map(|&alive| if alive { u8::MAX } else { 0 }));
+        true
+    }
+}
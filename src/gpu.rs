@@ -0,0 +1,460 @@
+//!GPU 计算着色器模拟
+//!细胞保存在两个存储缓冲区中交替读写(ping-pong)
+//!渲染前先把当前代的活细胞紧凑排列成序号列表,再用间接绘制只绘制活细胞
+//!CPU 端的 Board::step 作为参考实现,用于校验计算结果
+
+use wgpu::*;
+use wgpu::util::DeviceExt;
+
+use crate::board::Board;
+use crate::render::INDICES;
+use crate::rule::Rule;
+use crate::topology::Topology;
+
+//计算着色器的工作组大小,需要与 life.wgsl 保持一致
+const WORKGROUP_SIZE: u32 = 8;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct Params {
+    width: u32,
+    height: u32,
+    birth: u32,
+    survival: u32,
+    topology: u32,
+    //uniform 缓冲区按 16 字节对齐
+    _padding: [u32; 3],
+}
+
+//与 wgpu 的 DrawIndexedIndirect 参数布局一致,instance_count 由 cs_compact 累加
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct DrawArgs {
+    index_count: u32,
+    instance_count: u32,
+    first_index: u32,
+    base_vertex: i32,
+    first_instance: u32,
+}
+
+impl DrawArgs {
+    const RESET: DrawArgs = DrawArgs {
+        index_count: INDICES.len() as u32,
+        instance_count: 0,
+        first_index: 0,
+        base_vertex: 0,
+        first_instance: 0,
+    };
+}
+
+impl Params {
+    fn new(width: u32, height: u32, rule: &Rule, topology: Topology) -> Self {
+        let topology = match topology {
+            Topology::Bounded => 0,
+            Topology::Torus => 1,
+            Topology::KleinBottle => 2,
+            Topology::CrossSurface => 3,
+        };
+        Params {
+            width,
+            height,
+            birth: rule.birth_mask() as u32,
+            survival: rule.survival_mask() as u32,
+            topology,
+            _padding: [0; 3],
+        }
+    }
+}
+
+pub struct GpuLife {
+    width: u32,
+    height: u32,
+    params: Params,
+    params_buffer: Buffer,
+    //cell_buffers[current] 保存当前代
+    cell_buffers: [Buffer; 2],
+    //compute_bind_groups[i] 读取 cell_buffers[i] 并写入另一个缓冲区
+    compute_bind_groups: [BindGroup; 2],
+    compute_pipeline: ComputePipeline,
+    //间接绘制参数,活细胞序号列表只通过绑定组访问
+    draw_args_buffer: Buffer,
+    //compact_bind_groups[i] 从 cell_buffers[i] 收集活细胞
+    compact_bind_groups: [BindGroup; 2],
+    compact_pipeline: ComputePipeline,
+    render_bind_group_layout: BindGroupLayout,
+    render_bind_group: BindGroup,
+    //片元着色器按像素读取当前代,用于全屏网格渲染
+    cells_bind_group_layout: BindGroupLayout,
+    cells_bind_groups: [BindGroup; 2],
+    current: usize,
+}
+
+impl GpuLife {
+    pub fn new(device: &Device, board: &Board, rule: &Rule) -> Self {
+        let width = board.width() as u32;
+        let height = board.height() as u32;
+        let params = Params::new(width, height, rule, board.topology());
+
+        let params_buffer = device.create_buffer_init(&util::BufferInitDescriptor {
+            label: Some("Life Params Buffer"),
+            contents: bytemuck::bytes_of(&params),
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+        });
+
+        let cells = board_to_cells(board);
+        let cell_buffers = [0, 1].map(|i| {
+            device.create_buffer_init(&util::BufferInitDescriptor {
+                label: Some(&format!("Life Cell Buffer {}", i)),
+                contents: bytemuck::cast_slice(&cells),
+                usage: BufferUsages::STORAGE | BufferUsages::COPY_DST | BufferUsages::COPY_SRC,
+            })
+        });
+
+        let shader = device.create_shader_module(&ShaderModuleDescriptor {
+            label: Some("Life Compute Shader"),
+            source: ShaderSource::Wgsl(include_str!("life.wgsl").into()),
+        });
+
+        let compute_bind_group_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("Life Compute Bind Group Layout"),
+            entries: &[
+                uniform_entry(0, ShaderStages::COMPUTE),
+                storage_entry(1, ShaderStages::COMPUTE, true),
+                storage_entry(2, ShaderStages::COMPUTE, false),
+            ],
+        });
+
+        let compute_pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: Some("Life Compute Pipeline Layout"),
+            bind_group_layouts: &[&compute_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let compute_pipeline = device.create_compute_pipeline(&ComputePipelineDescriptor {
+            label: Some("Life Compute Pipeline"),
+            layout: Some(&compute_pipeline_layout),
+            module: &shader,
+            entry_point: "cs_main",
+        });
+
+        let compute_bind_groups = [0, 1].map(|i| {
+            device.create_bind_group(&BindGroupDescriptor {
+                label: Some(&format!("Life Compute Bind Group {}", i)),
+                layout: &compute_bind_group_layout,
+                entries: &[
+                    BindGroupEntry { binding: 0, resource: params_buffer.as_entire_binding() },
+                    BindGroupEntry { binding: 1, resource: cell_buffers[i].as_entire_binding() },
+                    BindGroupEntry { binding: 2, resource: cell_buffers[1 - i].as_entire_binding() },
+                ],
+            })
+        });
+
+        let draw_args_buffer = device.create_buffer_init(&util::BufferInitDescriptor {
+            label: Some("Life Draw Args Buffer"),
+            contents: bytemuck::bytes_of(&DrawArgs::RESET),
+            usage: BufferUsages::INDIRECT | BufferUsages::STORAGE | BufferUsages::COPY_DST | BufferUsages::COPY_SRC,
+        });
+
+        //最坏情况下所有细胞都是活的
+        let live_buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("Life Live Cell Buffer"),
+            size: (cells.len() * std::mem::size_of::<u32>()) as BufferAddress,
+            usage: BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        });
+
+        let compact_bind_group_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("Life Compact Bind Group Layout"),
+            entries: &[
+                uniform_entry(0, ShaderStages::COMPUTE),
+                storage_entry(1, ShaderStages::COMPUTE, true),
+                storage_entry(3, ShaderStages::COMPUTE, false),
+                storage_entry(4, ShaderStages::COMPUTE, false),
+            ],
+        });
+
+        let compact_pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: Some("Life Compact Pipeline Layout"),
+            bind_group_layouts: &[&compact_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let compact_pipeline = device.create_compute_pipeline(&ComputePipelineDescriptor {
+            label: Some("Life Compact Pipeline"),
+            layout: Some(&compact_pipeline_layout),
+            module: &shader,
+            entry_point: "cs_compact",
+        });
+
+        let compact_bind_groups = [0, 1].map(|i| {
+            device.create_bind_group(&BindGroupDescriptor {
+                label: Some(&format!("Life Compact Bind Group {}", i)),
+                layout: &compact_bind_group_layout,
+                entries: &[
+                    BindGroupEntry { binding: 0, resource: params_buffer.as_entire_binding() },
+                    BindGroupEntry { binding: 1, resource: cell_buffers[i].as_entire_binding() },
+                    BindGroupEntry { binding: 3, resource: draw_args_buffer.as_entire_binding() },
+                    BindGroupEntry { binding: 4, resource: live_buffer.as_entire_binding() },
+                ],
+            })
+        });
+
+        //渲染时顶点着色器只读访问参数与活细胞序号
+        let render_bind_group_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("Life Render Bind Group Layout"),
+            entries: &[
+                uniform_entry(0, ShaderStages::VERTEX),
+                storage_entry(1, ShaderStages::VERTEX, true),
+            ],
+        });
+
+        let render_bind_group = device.create_bind_group(&BindGroupDescriptor {
+            label: Some("Life Render Bind Group"),
+            layout: &render_bind_group_layout,
+            entries: &[
+                BindGroupEntry { binding: 0, resource: params_buffer.as_entire_binding() },
+                BindGroupEntry { binding: 1, resource: live_buffer.as_entire_binding() },
+            ],
+        });
+
+        let cells_bind_group_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("Life Cells Bind Group Layout"),
+            entries: &[storage_entry(0, ShaderStages::FRAGMENT, true)],
+        });
+
+        let cells_bind_groups = [0, 1].map(|i| {
+            device.create_bind_group(&BindGroupDescriptor {
+                label: Some(&format!("Life Cells Bind Group {}", i)),
+                layout: &cells_bind_group_layout,
+                entries: &[BindGroupEntry { binding: 0, resource: cell_buffers[i].as_entire_binding() }],
+            })
+        });
+
+        GpuLife {
+            width,
+            height,
+            params,
+            params_buffer,
+            cell_buffers,
+            compute_bind_groups,
+            compute_pipeline,
+            draw_args_buffer,
+            compact_bind_groups,
+            compact_pipeline,
+            render_bind_group_layout,
+            render_bind_group,
+            cells_bind_group_layout,
+            cells_bind_groups,
+            current: 0,
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    //细胞总数
+    pub fn cell_count(&self) -> u32 {
+        self.width * self.height
+    }
+
+    //上传整个棋盘,尺寸需要与创建时一致
+    pub fn upload(&self, queue: &Queue, board: &Board) {
+        assert_eq!((board.width() as u32, board.height() as u32), (self.width, self.height));
+        let cells = board_to_cells(board);
+        queue.write_buffer(&self.cell_buffers[self.current], 0, bytemuck::cast_slice(&cells));
+    }
+
+    //修改单个细胞,越界时忽略
+    pub fn set_cell(&self, queue: &Queue, x: usize, y: usize, alive: bool) {
+        if x < self.width as usize && y < self.height as usize {
+            let offset = ((y * self.width as usize + x) * std::mem::size_of::<u32>()) as BufferAddress;
+            queue.write_buffer(&self.cell_buffers[self.current], offset, bytemuck::bytes_of(&(alive as u32)));
+        }
+    }
+
+    //把多个细胞都设为 alive,越界的细胞被忽略
+    //同一行中连续的细胞合并为一次写入,一笔绘制只需要与笔刷高度相当的写入次数
+    pub fn set_cells(&self, queue: &Queue, cells: &[(usize, usize)], alive: bool) {
+        let (width, height) = (self.width as usize, self.height as usize);
+        let mut cells = cells.iter().copied().filter(|&(x, y)| x < width && y < height).collect::<Vec<_>>();
+        cells.sort_unstable_by_key(|&(x, y)| (y, x));
+        cells.dedup();
+        let values = vec![alive as u32; width];
+        let mut start = 0;
+        while start < cells.len() {
+            let (x, y) = cells[start];
+            let mut end = start + 1;
+            while end < cells.len() && cells[end] == (x + end - start, y) {
+                end += 1;
+            }
+            let offset = ((y * width + x) * std::mem::size_of::<u32>()) as BufferAddress;
+            queue.write_buffer(&self.cell_buffers[self.current], offset, bytemuck::cast_slice(&values[..end - start]));
+            start = end;
+        }
+    }
+
+    //读回单个细胞,越界时视为死细胞,会阻塞直到 GPU 完成
+    pub fn get_cell(&self, device: &Device, queue: &Queue, x: usize, y: usize) -> bool {
+        if x >= self.width as usize || y >= self.height as usize {
+            return false;
+        }
+        let size = std::mem::size_of::<u32>() as BufferAddress;
+        let offset = (y * self.width as usize + x) as BufferAddress * size;
+        let data = read_buffer(device, queue, &self.cell_buffers[self.current], offset, size);
+        bytemuck::pod_read_unaligned::<u32>(&data) != 0
+    }
+
+    //更新演化规则与边界拓扑
+    pub fn set_rule(&mut self, queue: &Queue, rule: &Rule, topology: Topology) {
+        self.params = Params::new(self.width, self.height, rule, topology);
+        queue.write_buffer(&self.params_buffer, 0, bytemuck::bytes_of(&self.params));
+    }
+
+    //在 encoder 中记录一次计算,演化一代
+    pub fn step(&mut self, encoder: &mut CommandEncoder) {
+        {
+            let mut compute_pass = encoder.begin_compute_pass(&ComputePassDescriptor {
+                label: Some("Life Compute Pass"),
+            });
+            compute_pass.set_pipeline(&self.compute_pipeline);
+            compute_pass.set_bind_group(0, &self.compute_bind_groups[self.current], &[]);
+            compute_pass.dispatch(
+                self.width.div_ceil(WORKGROUP_SIZE),
+                self.height.div_ceil(WORKGROUP_SIZE),
+                1,
+            );
+        }
+        self.current = 1 - self.current;
+    }
+
+    //在 encoder 中记录活细胞收集,之后可以用 draw_args_buffer 间接绘制当前代
+    pub fn compact(&self, queue: &Queue, encoder: &mut CommandEncoder) {
+        //write_buffer 会在本次提交的命令之前生效
+        queue.write_buffer(&self.draw_args_buffer, 0, bytemuck::bytes_of(&DrawArgs::RESET));
+        let mut compute_pass = encoder.begin_compute_pass(&ComputePassDescriptor {
+            label: Some("Life Compact Pass"),
+        });
+        compute_pass.set_pipeline(&self.compact_pipeline);
+        compute_pass.set_bind_group(0, &self.compact_bind_groups[self.current], &[]);
+        compute_pass.dispatch(
+            self.width.div_ceil(WORKGROUP_SIZE),
+            self.height.div_ceil(WORKGROUP_SIZE),
+            1,
+        );
+    }
+
+    //渲染活细胞所需的绑定组布局,用于创建渲染管线
+    pub fn render_bind_group_layout(&self) -> &BindGroupLayout {
+        &self.render_bind_group_layout
+    }
+
+    //渲染绑定组,包含最近一次 compact 收集的活细胞
+    pub fn render_bind_group(&self) -> &BindGroup {
+        &self.render_bind_group
+    }
+
+    //只包含当前代细胞缓冲区的绑定组布局,片元着色器可见
+    pub fn cells_bind_group_layout(&self) -> &BindGroupLayout {
+        &self.cells_bind_group_layout
+    }
+
+    //绑定当前代的细胞缓冲区
+    pub fn cells_bind_group(&self) -> &BindGroup {
+        &self.cells_bind_groups[self.current]
+    }
+
+    //draw_indexed_indirect 使用的参数缓冲区
+    pub fn draw_args_buffer(&self) -> &Buffer {
+        &self.draw_args_buffer
+    }
+
+    //读回最近一次 compact 收集到的活细胞数量,会阻塞直到 GPU 完成
+    pub fn read_instance_count(&self, device: &Device, queue: &Queue) -> u32 {
+        let size = std::mem::size_of::<DrawArgs>() as BufferAddress;
+        let data = read_buffer(device, queue, &self.draw_args_buffer, 0, size);
+        bytemuck::pod_read_unaligned::<DrawArgs>(&data).instance_count
+    }
+
+    //读回当前代,会阻塞直到 GPU 完成之前提交的所有计算
+    pub fn read_board(&self, device: &Device, queue: &Queue) -> Board {
+        let size = (self.cell_count() as usize * std::mem::size_of::<u32>()) as BufferAddress;
+        let data = read_buffer(device, queue, &self.cell_buffers[self.current], 0, size);
+
+        let topology = match self.params.topology {
+            1 => Topology::Torus,
+            2 => Topology::KleinBottle,
+            3 => Topology::CrossSurface,
+            _ => Topology::Bounded,
+        };
+        let mut board = Board::new(self.width as usize, self.height as usize).with_topology(topology);
+        let cells: &[u32] = bytemuck::cast_slice(&data);
+        for (i, &cell) in cells.iter().enumerate() {
+            board.set(i % self.width as usize, i / self.width as usize, cell != 0);
+        }
+        board
+    }
+}
+
+//把缓冲区从 offset 开始的 size 字节复制到暂存缓冲区并读回
+fn read_buffer(device: &Device, queue: &Queue, buffer: &Buffer, offset: BufferAddress, size: BufferAddress) -> Vec<u8> {
+    let staging_buffer = device.create_buffer(&BufferDescriptor {
+        label: Some("Life Staging Buffer"),
+        size,
+        usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+        label: Some("Life Readback Encoder"),
+    });
+    encoder.copy_buffer_to_buffer(buffer, offset, &staging_buffer, 0, size);
+    queue.submit(std::iter::once(encoder.finish()));
+    read_mapped(device, &staging_buffer)
+}
+
+//等待 GPU 完成后映射暂存缓冲区并复制出全部内容
+pub(crate) fn read_mapped(device: &Device, staging_buffer: &Buffer) -> Vec<u8> {
+    let slice = staging_buffer.slice(..);
+    let mapping = slice.map_async(MapMode::Read);
+    device.poll(Maintain::Wait);
+    pollster::block_on(mapping).expect("读取缓冲区失败");
+
+    let data = slice.get_mapped_range().to_vec();
+    staging_buffer.unmap();
+    data
+}
+
+fn board_to_cells(board: &Board) -> Vec<u32> {
+    board.cells().iter().map(|&alive| alive as u32).collect()
+}
+
+fn uniform_entry(binding: u32, visibility: ShaderStages) -> BindGroupLayoutEntry {
+    BindGroupLayoutEntry {
+        binding,
+        visibility,
+        ty: BindingType::Buffer {
+            ty: BufferBindingType::Uniform,
+            has_dynamic_offset: false,
+            min_binding_size: BufferSize::new(std::mem::size_of::<Params>() as _),
+        },
+        count: None,
+    }
+}
+
+fn storage_entry(binding: u32, visibility: ShaderStages, read_only: bool) -> BindGroupLayoutEntry {
+    BindGroupLayoutEntry {
+        binding,
+        visibility,
+        ty: BindingType::Buffer {
+            ty: BufferBindingType::Storage { read_only },
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    }
+}
@@ -0,0 +1,241 @@
+//!基于纹理的全屏网格渲染
+//!棋盘以每个细胞一个字节的 R8 纹理上传,用一个全屏三角形在片元着色器中绘制
+//!与实例化渲染相比每帧只需要上传 宽x高 个字节,适合百万细胞级别的棋盘
+//!GPU 模拟时片元着色器直接读取细胞缓冲区,不需要纹理
+
+use std::num::NonZeroU32;
+
+use wgpu::*;
+use wgpu::util::DeviceExt;
+
+use crate::board::Board;
+use crate::camera::Camera;
+use crate::gpu::GpuLife;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct GridParams {
+    width: u32,
+    height: u32,
+    grid_lines: u32,
+    //uniform 缓冲区按 16 字节对齐
+    _padding: u32,
+}
+
+pub struct GridRenderer {
+    width: u32,
+    height: u32,
+    params: GridParams,
+    params_buffer: Buffer,
+    //GPU 模拟时只是 1x1 的占位纹理
+    texture: Texture,
+    //bind_groups[0] 使用最近邻采样,bind_groups[1] 使用线性采样
+    bind_groups: [BindGroup; 2],
+    filter: FilterMode,
+    pipeline: RenderPipeline,
+    //是否从 GPU 模拟的细胞缓冲区读取
+    reads_cells: bool,
+}
+
+impl GridRenderer {
+    //创建网格渲染器,传入 life 时从 GPU 模拟的细胞缓冲区读取,否则需要调用 upload 上传棋盘
+    pub fn new(
+        device: &Device,
+        format: TextureFormat,
+        camera: &Camera,
+        width: u32,
+        height: u32,
+        life: Option<&GpuLife>,
+    ) -> Self {
+        let params = GridParams { width, height, grid_lines: 0, _padding: 0 };
+        let params_buffer = device.create_buffer_init(&util::BufferInitDescriptor {
+            label: Some("Grid Params Buffer"),
+            contents: bytemuck::bytes_of(&params),
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+        });
+
+        let texture_size = match life {
+            Some(_) => Extent3d { width: 1, height: 1, depth_or_array_layers: 1 },
+            None => Extent3d { width, height, depth_or_array_layers: 1 },
+        };
+        let texture = device.create_texture(&TextureDescriptor {
+            label: Some("Grid Board Texture"),
+            size: texture_size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::R8Unorm,
+            usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+        });
+        let view = texture.create_view(&TextureViewDescriptor::default());
+
+        let bind_group_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("Grid Bind Group Layout"),
+            entries: &[
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: BufferSize::new(std::mem::size_of::<GridParams>() as _),
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Texture {
+                        sample_type: TextureSampleType::Float { filterable: true },
+                        view_dimension: TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Sampler(SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        let bind_groups = [FilterMode::Nearest, FilterMode::Linear].map(|filter| {
+            let sampler = device.create_sampler(&SamplerDescriptor {
+                label: Some("Grid Sampler"),
+                address_mode_u: AddressMode::ClampToEdge,
+                address_mode_v: AddressMode::ClampToEdge,
+                mag_filter: filter,
+                min_filter: filter,
+                ..Default::default()
+            });
+            device.create_bind_group(&BindGroupDescriptor {
+                label: Some("Grid Bind Group"),
+                layout: &bind_group_layout,
+                entries: &[
+                    BindGroupEntry { binding: 0, resource: params_buffer.as_entire_binding() },
+                    BindGroupEntry { binding: 1, resource: BindingResource::TextureView(&view) },
+                    BindGroupEntry { binding: 2, resource: BindingResource::Sampler(&sampler) },
+                ],
+            })
+        });
+
+        let shader = device.create_shader_module(&ShaderModuleDescriptor {
+            label: Some("Grid Shader"),
+            source: ShaderSource::Wgsl(include_str!("grid.wgsl").into()),
+        });
+
+        let mut bind_group_layouts = vec![camera.bind_group_layout(), &bind_group_layout];
+        bind_group_layouts.extend(life.map(GpuLife::cells_bind_group_layout));
+        let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: Some("Grid Pipeline Layout"),
+            bind_group_layouts: &bind_group_layouts,
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+            label: Some("Grid Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: VertexState {
+                module: &shader,
+                entry_point: "vs_fullscreen",
+                buffers: &[],
+            },
+            fragment: Some(FragmentState {
+                module: &shader,
+                entry_point: if life.is_some() { "fs_grid_cells" } else { "fs_grid" },
+                targets: &[ColorTargetState {
+                    format,
+                    blend: Some(BlendState::REPLACE),
+                    write_mask: ColorWrites::ALL,
+                }],
+            }),
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+            multiview: None,
+        });
+
+        GridRenderer {
+            width,
+            height,
+            params,
+            params_buffer,
+            texture,
+            bind_groups,
+            filter: FilterMode::Nearest,
+            pipeline,
+            reads_cells: life.is_some(),
+        }
+    }
+
+    //上传整个棋盘,尺寸需要与创建时一致
+    pub fn upload(&self, queue: &Queue, board: &Board) {
+        assert_eq!((board.width() as u32, board.height() as u32), (self.width, self.height));
+        self.upload_texels(queue, &board_to_texels(board));
+    }
+
+    //上传 board_to_texels 格式的纹理数据,长度需要等于创建时的宽 x 高
+    pub fn upload_texels(&self, queue: &Queue, texels: &[u8]) {
+        assert_eq!(texels.len(), self.width as usize * self.height as usize);
+        if self.reads_cells {
+            return;
+        }
+        queue.write_texture(
+            ImageCopyTexture {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: Origin3d::ZERO,
+                aspect: TextureAspect::All,
+            },
+            texels,
+            ImageDataLayout {
+                offset: 0,
+                bytes_per_row: NonZeroU32::new(self.width),
+                rows_per_image: None,
+            },
+            Extent3d { width: self.width, height: self.height, depth_or_array_layers: 1 },
+        );
+    }
+
+    pub fn grid_lines(&self) -> bool {
+        self.params.grid_lines != 0
+    }
+
+    //显示或隐藏网格线,细胞太小时着色器不会绘制网格线
+    pub fn set_grid_lines(&mut self, queue: &Queue, grid_lines: bool) {
+        self.params.grid_lines = grid_lines as u32;
+        queue.write_buffer(&self.params_buffer, 0, bytemuck::bytes_of(&self.params));
+    }
+
+    pub fn filter(&self) -> FilterMode {
+        self.filter
+    }
+
+    //纹理采样方式,只影响从纹理读取的情况
+    pub fn set_filter(&mut self, filter: FilterMode) {
+        self.filter = filter;
+    }
+
+    //在 render_pass 中绘制整个棋盘,GPU 模拟时需要传入创建时使用的 life
+    pub fn draw<'a>(&'a self, render_pass: &mut RenderPass<'a>, camera: &'a Camera, life: Option<&'a GpuLife>) {
+        assert_eq!(life.is_some(), self.reads_cells, "GridRenderer 创建时与绘制时的细胞来源不一致");
+        let bind_group = match self.filter {
+            FilterMode::Nearest => &self.bind_groups[0],
+            FilterMode::Linear => &self.bind_groups[1],
+        };
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, camera.bind_group(), &[]);
+        render_pass.set_bind_group(1, bind_group, &[]);
+        if let Some(life) = life {
+            render_pass.set_bind_group(2, life.cells_bind_group(), &[]);
+        }
+        render_pass.draw(0..3, 0..1);
+    }
+}
+
+//每个细胞一个字节,活细胞为 255,按行从下到上排列,与 Board 的存储顺序一致
+pub fn board_to_texels(board: &Board) -> Vec<u8> {
+    board.cells().iter().map(|&alive| if alive { u8::MAX } else { 0 }).collect()
+}
@@ -0,0 +1,81 @@
+// 全屏网格渲染
+// 一个覆盖整个屏幕的三角形,片元着色器用相机的逆矩阵求出像素所在的棋盘坐标,再查找对应的细胞
+// fs_grid 从 R8 纹理采样,fs_grid_cells 直接读取 GPU 模拟的细胞缓冲区
+
+struct Camera {
+    view_proj: mat4x4<f32>;
+    inverse_view_proj: mat4x4<f32>;
+};
+
+struct GridParams {
+    width: u32;
+    height: u32;
+    grid_lines: u32;
+};
+
+struct Cells {
+    cells: array<u32>;
+};
+
+struct VertexOutput {
+    [[builtin(position)]] clip_position: vec4<f32>;
+    // 裁剪空间坐标,在片元着色器中换算为棋盘坐标
+    [[location(0)]] position: vec2<f32>;
+};
+
+[[group(0), binding(0)]] var<uniform> camera: Camera;
+[[group(1), binding(0)]] var<uniform> grid: GridParams;
+[[group(1), binding(1)]] var board: texture_2d<f32>;
+[[group(1), binding(2)]] var board_sampler: sampler;
+[[group(2), binding(0)]] var<storage, read> cells: Cells;
+
+[[stage(vertex)]]
+fn vs_fullscreen([[builtin(vertex_index)]] index: u32) -> VertexOutput {
+    // 三个顶点 (-1, -1)、(3, -1)、(-1, 3) 覆盖整个裁剪空间
+    let position = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u)) * 2.0 - 1.0;
+
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(position, 0.0, 1.0);
+    out.position = position;
+    return out;
+}
+
+// 像素对应的棋盘坐标,以细胞为单位
+fn board_position(position: vec2<f32>) -> vec2<f32> {
+    return (camera.inverse_view_proj * vec4<f32>(position, 0.0, 1.0)).xy;
+}
+
+// 按细胞亮度着色,并在细胞边缘叠加网格线,棋盘之外为背景色
+fn shade(alive: f32, cell: vec2<f32>, pixel: vec2<f32>) -> vec4<f32> {
+    let size = vec2<f32>(f32(grid.width), f32(grid.height));
+    if (cell.x < 0.0 || cell.y < 0.0 || cell.x >= size.x || cell.y >= size.y) {
+        return vec4<f32>(0.0, 0.0, 0.0, 1.0);
+    }
+
+    let edge = fract(cell);
+    var color = vec3<f32>(alive);
+    // 细胞小于 4 个像素时不画网格线,否则画面会被线条填满
+    if (grid.grid_lines != 0u && max(pixel.x, pixel.y) < 0.25 && (edge.x < pixel.x || edge.y < pixel.y)) {
+        color = mix(color, vec3<f32>(0.25), 0.5);
+    }
+    return vec4<f32>(color, 1.0);
+}
+
+[[stage(fragment)]]
+fn fs_grid(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    let cell = board_position(in.position);
+    // 导数与纹理采样需要在统一控制流中计算
+    let pixel = fwidth(cell);
+    let alive = textureSample(board, board_sampler, cell / vec2<f32>(f32(grid.width), f32(grid.height))).r;
+    return shade(alive, cell, pixel);
+}
+
+[[stage(fragment)]]
+fn fs_grid_cells(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    let cell = board_position(in.position);
+    let pixel = fwidth(cell);
+    let size = vec2<u32>(grid.width, grid.height);
+    let index = min(vec2<u32>(max(cell, vec2<f32>(0.0))), size - vec2<u32>(1u, 1u));
+    let alive = f32(cells.cells[index.y * grid.width + index.x]);
+    return shade(alive, cell, pixel);
+}
@@ -0,0 +1,471 @@
+//!HashLife 引擎
+//!宇宙是一棵四叉树,内容相同的节点只保存一份(规范化),每个节点的演化结果都会缓存
+//!大小为 2^k 的节点可以一次演化 2^(k-2) 代,对于有规律的大图样可以在极短时间内跳过天文数字的代数
+//!节点只增不减,超过内存上限时在两次演化之间回收根节点不再引用的节点
+//!与稀疏宇宙一样没有边界,不支持 B0 规则
+
+use std::collections::HashMap;
+
+use crate::board::Board;
+use crate::engine::Engine;
+use crate::rule::Rule;
+
+//一次演化最多跳过 2^MAX_STEP_LOG2 代,保证坐标不会超出 i64
+pub const MAX_STEP_LOG2: u32 = 56;
+//默认内存上限
+pub const DEFAULT_MEMORY_LIMIT: usize = 1 << 30;
+//每个节点大约占用的内存,包括节点本身、去重表与结果缓存中的条目
+const BYTES_PER_NODE: usize = 96;
+//根节点的最小层数,3 层即 8x8
+const MIN_LEVEL: u8 = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct NodeId(u32);
+
+//两个叶子节点固定为 0 和 1
+const DEAD: NodeId = NodeId(0);
+const ALIVE: NodeId = NodeId(1);
+
+#[derive(Clone, Copy, Debug)]
+struct Node {
+    //大小为 2^level,叶子节点为 0 层
+    level: u8,
+    //西北、东北、西南、东南四个子节点,叶子节点没有子节点
+    children: [NodeId; 4],
+    population: u64,
+}
+
+pub struct HashLife {
+    nodes: Vec<Node>,
+    //子节点到节点的映射,保证内容相同的节点只有一个
+    canonical: HashMap<[NodeId; 4], NodeId>,
+    //(节点, 步长指数) 到演化结果的缓存
+    results: HashMap<(NodeId, u8), NodeId>,
+    //结果缓存对应的规则,规则变化时清空
+    cached_rule: Option<Rule>,
+    //各层的空节点
+    empty: Vec<NodeId>,
+    root: NodeId,
+    //根节点左下角的坐标
+    origin: (i64, i64),
+    max_nodes: usize,
+}
+
+impl Default for HashLife {
+    fn default() -> Self {
+        HashLife::new()
+    }
+}
+
+impl HashLife {
+    //创建一个空的宇宙
+    pub fn new() -> Self {
+        let leaf = |population| Node { level: 0, children: [DEAD; 4], population };
+        let mut life = HashLife {
+            nodes: vec![leaf(0), leaf(1)],
+            canonical: HashMap::new(),
+            results: HashMap::new(),
+            cached_rule: None,
+            empty: vec![DEAD],
+            root: DEAD,
+            origin: (0, 0),
+            max_nodes: DEFAULT_MEMORY_LIMIT / BYTES_PER_NODE,
+        };
+        life.root = life.empty_node(MIN_LEVEL);
+        life
+    }
+
+    //由活细胞坐标创建
+    pub fn from_cells<I: IntoIterator<Item = (i64, i64)>>(cells: I) -> Self {
+        let mut life = HashLife::new();
+        let cells: Vec<_> = cells.into_iter().collect();
+        let bounds = cells.iter().fold(None, |bounds, &(x, y)| {
+            Some(match bounds {
+                Some((min_x, min_y, max_x, max_y)) => (x.min(min_x), y.min(min_y), x.max(max_x), y.max(max_y)),
+                None => (x, y, x, y),
+            })
+        });
+        let Some((min_x, min_y, max_x, max_y)) = bounds else {
+            return life;
+        };
+        //包围盒的边长减一,坐标相距很远时也不会溢出
+        let span = max_x.abs_diff(min_x).max(max_y.abs_diff(min_y));
+        let level = (u64::BITS - span.leading_zeros()).max(MIN_LEVEL as u32) as u8;
+
+        let local: Vec<_> = cells.iter().map(|&(x, y)| (x.abs_diff(min_x), y.abs_diff(min_y))).collect();
+        life.root = life.build(level, &local);
+        life.origin = (min_x, min_y);
+        life
+    }
+
+    //复制稠密棋盘上的活细胞,坐标保持不变
+    pub fn from_board(board: &Board) -> Self {
+        HashLife::from_cells(board.live_cells())
+    }
+
+    //节点缓存的内存上限(字节),超过时在下一次演化前回收
+    pub fn set_memory_limit(&mut self, bytes: usize) {
+        self.max_nodes = (bytes / BYTES_PER_NODE).max(1024);
+    }
+
+    pub fn memory_limit(&self) -> usize {
+        self.max_nodes * BYTES_PER_NODE
+    }
+
+    //当前保存的节点数量,包括已经不再使用但还没有回收的节点
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    //演化 2^log2 代
+    pub fn step_pow2(&mut self, rule: &Rule, log2: u32) {
+        assert!(log2 <= MAX_STEP_LOG2, "一次最多演化 2^{} 代", MAX_STEP_LOG2);
+        if self.cached_rule != Some(*rule) {
+            self.results.clear();
+            self.cached_rule = Some(*rule);
+        }
+        if self.nodes.len() > self.max_nodes {
+            self.collect_garbage();
+        }
+
+        //图样需要位于根节点中央 1/4 的范围内,演化时向外扩张也不会超出结果的范围
+        while (self.level(self.root) as u32) < log2 + 3 || !self.is_centered(self.root) {
+            self.expand();
+        }
+        let quarter = 1i64 << (self.level(self.root) - 2);
+        self.root = self.successor(self.root, log2 as u8);
+        self.origin = (self.origin.0 + quarter, self.origin.1 + quarter);
+    }
+
+    //只保留根节点引用的节点,并清空结果缓存
+    pub fn collect_garbage(&mut self) {
+        let old = std::mem::take(&mut self.nodes);
+        self.nodes = old[..2].to_vec();
+        self.canonical.clear();
+        self.results.clear();
+        self.empty = vec![DEAD];
+
+        let mut remap = HashMap::new();
+        self.root = self.copy_node(&old, self.root, &mut remap);
+    }
+
+    fn copy_node(&mut self, old: &[Node], id: NodeId, remap: &mut HashMap<NodeId, NodeId>) -> NodeId {
+        if id == DEAD || id == ALIVE {
+            return id;
+        }
+        if let Some(&new) = remap.get(&id) {
+            return new;
+        }
+        let children = old[id.0 as usize].children.map(|child| self.copy_node(old, child, remap));
+        let new = self.join(children);
+        remap.insert(id, new);
+        new
+    }
+
+    fn node(&self, id: NodeId) -> &Node {
+        &self.nodes[id.0 as usize]
+    }
+
+    fn level(&self, id: NodeId) -> u8 {
+        self.node(id).level
+    }
+
+    fn children(&self, id: NodeId) -> [NodeId; 4] {
+        self.node(id).children
+    }
+
+    //由四个同层的子节点得到规范化的父节点
+    fn join(&mut self, children: [NodeId; 4]) -> NodeId {
+        if let Some(&id) = self.canonical.get(&children) {
+            return id;
+        }
+        let node = Node {
+            level: self.level(children[0]) + 1,
+            children,
+            population: children.iter().map(|&child| self.node(child).population).sum(),
+        };
+        let id = NodeId(self.nodes.len() as u32);
+        self.nodes.push(node);
+        self.canonical.insert(children, id);
+        id
+    }
+
+    fn empty_node(&mut self, level: u8) -> NodeId {
+        while self.empty.len() <= level as usize {
+            let below = *self.empty.last().unwrap();
+            let node = self.join([below; 4]);
+            self.empty.push(node);
+        }
+        self.empty[level as usize]
+    }
+
+    //由相对于左下角的坐标建立节点
+    fn build(&mut self, level: u8, cells: &[(u64, u64)]) -> NodeId {
+        if cells.is_empty() {
+            return self.empty_node(level);
+        }
+        if level == 0 {
+            return ALIVE;
+        }
+        let half = 1u64 << (level - 1);
+        let mut quadrants: [Vec<(u64, u64)>; 4] = Default::default();
+        for &(x, y) in cells {
+            quadrants[quadrant(x, y, half)].push((x % half, y % half));
+        }
+        let children = [0, 1, 2, 3].map(|i| self.build(level - 1, &quadrants[i]));
+        self.join(children)
+    }
+
+    //中央 1/4 范围内的活细胞数量是否等于总数
+    fn is_centered(&self, id: NodeId) -> bool {
+        let [nw, ne, sw, se] = self.children(id);
+        let center = [(nw, 3), (ne, 2), (sw, 1), (se, 0)]
+            .iter()
+            .map(|&(child, inner)| {
+                let grandchild = self.children(child)[inner];
+                self.node(self.children(grandchild)[inner]).population
+            })
+            .sum::<u64>();
+        center == self.node(id).population
+    }
+
+    //根节点扩大一倍,原来的内容位于中央
+    fn expand(&mut self) {
+        let level = self.level(self.root);
+        let empty = self.empty_node(level - 1);
+        let [nw, ne, sw, se] = self.children(self.root);
+        let children = [
+            self.join([empty, empty, empty, nw]),
+            self.join([empty, empty, ne, empty]),
+            self.join([empty, sw, empty, empty]),
+            self.join([se, empty, empty, empty]),
+        ];
+        self.root = self.join(children);
+        let half = 1i64 << (level - 1);
+        self.origin = (self.origin.0 - half, self.origin.1 - half);
+    }
+
+    //k 层节点中央 k-1 层的部分在 2^min(log2, k-2) 代之后的状态
+    fn successor(&mut self, id: NodeId, log2: u8) -> NodeId {
+        let node = *self.node(id);
+        if node.population == 0 {
+            return self.empty_node(node.level - 1);
+        }
+        let log2 = log2.min(node.level - 2);
+        if let Some(&result) = self.results.get(&(id, log2)) {
+            return result;
+        }
+
+        let result = if node.level == 2 {
+            self.step_4x4(id)
+        } else {
+            let [nw, ne, sw, se] = node.children;
+            let [_, nw_ne, nw_sw, nw_se] = self.children(nw);
+            let [ne_nw, _, ne_sw, ne_se] = self.children(ne);
+            let [sw_nw, sw_ne, _, sw_se] = self.children(sw);
+            let [se_nw, se_ne, se_sw, _] = self.children(se);
+
+            //九个相互重叠的 k-1 层节点,按从上到下、从左到右排列
+            let n01 = self.join([nw_ne, ne_nw, nw_se, ne_sw]);
+            let n10 = self.join([nw_sw, nw_se, sw_nw, sw_ne]);
+            let n11 = self.join([nw_se, ne_sw, sw_ne, se_nw]);
+            let n12 = self.join([ne_sw, ne_se, se_nw, se_ne]);
+            let n21 = self.join([sw_ne, se_nw, sw_se, se_sw]);
+            let c = [nw, n01, ne, n10, n11, n12, sw, n21, se].map(|n| self.successor(n, log2));
+
+            if log2 < node.level - 2 {
+                //只演化 2^log2 代,直接拼出中央部分
+                let part = |life: &HashLife, i: usize, quadrant: usize| life.children(c[i])[quadrant];
+                let children = [
+                    [part(self, 0, 3), part(self, 1, 2), part(self, 3, 1), part(self, 4, 0)],
+                    [part(self, 1, 3), part(self, 2, 2), part(self, 4, 1), part(self, 5, 0)],
+                    [part(self, 3, 3), part(self, 4, 2), part(self, 6, 1), part(self, 7, 0)],
+                    [part(self, 4, 3), part(self, 5, 2), part(self, 7, 1), part(self, 8, 0)],
+                ];
+                let children = children.map(|quadrant| self.join(quadrant));
+                self.join(children)
+            } else {
+                //再演化一次,总共 2^(k-2) 代
+                let children = [[0, 1, 3, 4], [1, 2, 4, 5], [3, 4, 6, 7], [4, 5, 7, 8]].map(|[a, b, d, e]| {
+                    let joined = self.join([c[a], c[b], c[d], c[e]]);
+                    self.successor(joined, log2)
+                });
+                self.join(children)
+            }
+        };
+        self.results.insert((id, log2), result);
+        result
+    }
+
+    //2 层节点(4x4)中央 2x2 在一代之后的状态
+    fn step_4x4(&mut self, id: NodeId) -> NodeId {
+        let rule = self.cached_rule.unwrap_or_default();
+        //第 y 行第 x 列的细胞对应第 y * 4 + x 位
+        let mut bits = 0u16;
+        for y in 0..4 {
+            for x in 0..4 {
+                if self.cell(id, x, y) {
+                    bits |= 1 << (y * 4 + x);
+                }
+            }
+        }
+        let next = |x: u64, y: u64| {
+            let alive = bits >> (y * 4 + x) & 1 == 1;
+            //以 (x, y) 为中心的 3x3 区域
+            let block = bits >> ((y - 1) * 4 + x - 1) & 0x777;
+            let neighbors = block.count_ones() as usize - alive as usize;
+            if rule.next_state(alive, neighbors) { ALIVE } else { DEAD }
+        };
+        let children = [next(1, 2), next(2, 2), next(1, 1), next(2, 1)];
+        self.join(children)
+    }
+
+    //节点内相对于左下角的坐标处的细胞
+    fn cell(&self, mut id: NodeId, mut x: u64, mut y: u64) -> bool {
+        loop {
+            let node = self.node(id);
+            if node.population == 0 {
+                return false;
+            }
+            if node.level == 0 {
+                return true;
+            }
+            let half = 1u64 << (node.level - 1);
+            id = node.children[quadrant(x, y, half)];
+            x %= half;
+            y %= half;
+        }
+    }
+
+    fn set_cell(&mut self, id: NodeId, x: u64, y: u64, alive: bool) -> NodeId {
+        let level = self.level(id);
+        if level == 0 {
+            return if alive { ALIVE } else { DEAD };
+        }
+        let half = 1u64 << (level - 1);
+        let mut children = self.children(id);
+        let i = quadrant(x, y, half);
+        children[i] = self.set_cell(children[i], x % half, y % half, alive);
+        self.join(children)
+    }
+
+    //坐标相对于根节点左下角的位置,不在根节点内时为 None
+    fn local(&self, x: i64, y: i64) -> Option<(u64, u64)> {
+        let size = 1i128 << self.level(self.root);
+        let (dx, dy) = (x as i128 - self.origin.0 as i128, y as i128 - self.origin.1 as i128);
+        ((0..size).contains(&dx) && (0..size).contains(&dy)).then_some((dx as u64, dy as u64))
+    }
+
+    //节点内活细胞的范围 (最小 x, 最小 y, 最大 x, 最大 y),相对于左下角
+    //相同的节点只计算一次,耗时与节点数量成正比,与活细胞数量无关
+    fn extent(&self, id: NodeId, memo: &mut HashMap<NodeId, (u64, u64, u64, u64)>) -> Option<(u64, u64, u64, u64)> {
+        let node = *self.node(id);
+        if node.population == 0 {
+            return None;
+        }
+        if node.level == 0 {
+            return Some((0, 0, 0, 0));
+        }
+        if let Some(&extent) = memo.get(&id) {
+            return Some(extent);
+        }
+        let half = 1u64 << (node.level - 1);
+        let offsets = [(0, half), (half, half), (0, 0), (half, 0)];
+        let extent = node
+            .children
+            .iter()
+            .zip(offsets)
+            .filter_map(|(&child, (dx, dy))| {
+                self.extent(child, memo)
+                    .map(|(min_x, min_y, max_x, max_y)| (min_x + dx, min_y + dy, max_x + dx, max_y + dy))
+            })
+            .reduce(|a, b| (a.0.min(b.0), a.1.min(b.1), a.2.max(b.2), a.3.max(b.3)))?;
+        memo.insert(id, extent);
+        Some(extent)
+    }
+
+    fn collect_cells(&self, id: NodeId, origin: (i64, i64), min: (i64, i64), max: (i64, i64), cells: &mut Vec<(i64, i64)>) {
+        let node = self.node(id);
+        if node.population == 0 {
+            return;
+        }
+        let last = (1i64 << node.level) - 1;
+        if origin.0 > max.0 || origin.1 > max.1 || origin.0 + last < min.0 || origin.1 + last < min.1 {
+            return;
+        }
+        if node.level == 0 {
+            cells.push(origin);
+            return;
+        }
+        let half = 1i64 << (node.level - 1);
+        let [nw, ne, sw, se] = node.children;
+        self.collect_cells(nw, (origin.0, origin.1 + half), min, max, cells);
+        self.collect_cells(ne, (origin.0 + half, origin.1 + half), min, max, cells);
+        self.collect_cells(sw, origin, min, max, cells);
+        self.collect_cells(se, (origin.0 + half, origin.1), min, max, cells);
+    }
+}
+
+//子节点的序号:西北 0、东北 1、西南 2、东南 3,y 轴向上
+fn quadrant(x: u64, y: u64, half: u64) -> usize {
+    match (x >= half, y >= half) {
+        (false, true) => 0,
+        (true, true) => 1,
+        (false, false) => 2,
+        (true, false) => 3,
+    }
+}
+
+impl Engine for HashLife {
+    fn step(&mut self, rule: &Rule) {
+        self.step_pow2(rule, 0);
+    }
+
+    //按二进制拆分代数,每一位用一次 step_pow2
+    fn advance(&mut self, rule: &Rule, generations: u64) {
+        for log2 in (0..u64::BITS).rev().filter(|&log2| generations >> log2 & 1 == 1) {
+            if log2 > MAX_STEP_LOG2 {
+                for _ in 0..1u64 << (log2 - MAX_STEP_LOG2) {
+                    self.step_pow2(rule, MAX_STEP_LOG2);
+                }
+            } else {
+                self.step_pow2(rule, log2);
+            }
+        }
+    }
+
+    fn get(&self, x: i64, y: i64) -> bool {
+        self.local(x, y).is_some_and(|(x, y)| self.cell(self.root, x, y))
+    }
+
+    fn set(&mut self, x: i64, y: i64, alive: bool) {
+        if alive {
+            while self.local(x, y).is_none() {
+                self.expand();
+            }
+        }
+        if let Some((x, y)) = self.local(x, y) {
+            self.root = self.set_cell(self.root, x, y, alive);
+        }
+    }
+
+    fn population(&self) -> usize {
+        self.node(self.root).population as usize
+    }
+
+    fn live_cells(&self) -> Vec<(i64, i64)> {
+        self.live_cells_in((i64::MIN, i64::MIN), (i64::MAX, i64::MAX))
+    }
+
+    fn bounding_box(&self) -> Option<(i64, i64, i64, i64)> {
+        let (min_x, min_y, max_x, max_y) = self.extent(self.root, &mut HashMap::new())?;
+        let (x, y) = self.origin;
+        Some((x + min_x as i64, y + min_y as i64, x + max_x as i64, y + max_y as i64))
+    }
+
+    fn live_cells_in(&self, min: (i64, i64), max: (i64, i64)) -> Vec<(i64, i64)> {
+        let mut cells = Vec::new();
+        self.collect_cells(self.root, self.origin, min, max, &mut cells);
+        cells
+    }
+}
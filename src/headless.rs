@@ -0,0 +1,239 @@
+//!无窗口模式
+//!不创建窗口与 wgpu 设备,在 CPU 上演化指定代数后输出结果与统计信息
+//!截图与录制的画面也由 raster 模块在 CPU 上绘制,指定 --snapshot-gpu 时截图改用 wgpu 离屏渲染
+
+use std::path::Path;
+use std::time::Instant;
+
+use conway_wgpu::raster::{self, RasterOptions};
+use conway_wgpu::record::AnimationWriter;
+use conway_wgpu::{pattern, snapshot, Board, PeriodDetector, Rule, Statistics};
+
+use crate::cli::{Initial, Options};
+
+//未指定 --gens 时演化的代数
+const DEFAULT_GENERATIONS: u64 = 1000;
+//截图最长边的目标像素数,每个细胞取整数个像素
+const SNAPSHOT_LONG_SIDE: usize = 512;
+//截图与录制的每一帧图像的像素总数上限
+const MAX_IMAGE_PIXELS: usize = 1 << 26;
+
+pub fn run(options: &Options) -> Result<(), String> {
+    if options.gpu {
+        return Err("run 模式只使用 CPU 演化,不支持 --gpu".to_string());
+    }
+
+    let (initial, rule) = options.initial()?;
+    let (width, height) = initial.size();
+    //只指定录制范围时演化到范围的最后一代
+    let generations = options
+        .generations
+        .or(options.record_range.map(|(_, end)| end - 1))
+        .unwrap_or(DEFAULT_GENERATIONS);
+
+    let record_range = match &options.record {
+        Some(_) => {
+            let (first, end) = options.record_range.unwrap_or((0, generations + 1));
+            if first > generations {
+                return Err(format!("录制范围从第 {} 代开始,超过了演化的代数 {}", first, generations));
+            }
+            Some((first, end.min(generations + 1)))
+        }
+        None => None,
+    };
+    //事先确定画面的范围与帧数,每一代画好后立即编码,不在内存中保存所有帧
+    let mut animation = match (&options.record, record_range) {
+        (Some(path), Some((first, end))) => {
+            //有界棋盘录制整个棋盘,无限宇宙录制所有帧活细胞的包围盒,保证每帧一样大
+            let bounds = match options.engine.is_unbounded() {
+                true => recording_bounds(options, initial.clone(), &rule, first, end)?,
+                false => Some((0, 0, width as i64 - 1, height as i64 - 1)),
+            };
+            let (min_x, min_y, max_x, max_y) = bounds.unwrap_or((0, 0, 0, 0));
+            let (frame_width, frame_height) = (max_x.abs_diff(min_x) as usize + 1, max_y.abs_diff(min_y) as usize + 1);
+            //先检查画面尺寸,避免分配棋盘或图像失败直接中止程序
+            let cell_size = options.record_options.raster.cell_size.max(1) as usize;
+            check_image_size("录制", frame_width, frame_height, cell_size)?;
+            let frame = Board::new(frame_width, frame_height);
+            let mut record_options = options.record_options;
+            record_options.raster.colors = options.colors;
+            record_options.raster.grid_lines = options.grid_lines;
+            let (image_width, image_height) = record_options.raster.image_size(&frame);
+            let frames = u32::try_from(end - first).map_err(|_| format!("录制的帧数 {} 过多", end - first))?;
+            let writer = AnimationWriter::create(path, image_width, image_height, frames, record_options)
+                .map_err(|e| format!("无法保存 `{}`: {}", path.display(), e))?;
+            Some((writer, frame, (min_x, min_y)))
+        }
+        _ => None,
+    };
+    let mut engine = options.create_engine(initial)?;
+    let initial_population = engine.population();
+    let mut detector = options.period.then(PeriodDetector::new);
+    let mut period = None;
+    let mut statistics = options.stats.as_ref().map(|_| Statistics::new());
+
+    let start = Instant::now();
+    let mut generation = 0;
+    loop {
+        let recording = record_range.is_some_and(|(first, end)| (first..end).contains(&generation));
+        let every_generation = detector.is_some() || statistics.is_some();
+        if recording || every_generation {
+            let cells = engine.live_cells();
+            if let Some(detector) = &mut detector {
+                period = detector.observe(generation, &cells);
+            }
+            if let Some(statistics) = &mut statistics {
+                statistics.observe(generation, &cells);
+            }
+            match &mut animation {
+                Some((writer, frame, (min_x, min_y))) if recording => {
+                    frame.clear();
+                    for &(x, y) in &cells {
+                        frame.set((x - *min_x) as usize, (y - *min_y) as usize, true);
+                    }
+                    writer.write_board(frame).map_err(|e| e.to_string())?;
+                }
+                _ => {}
+            }
+        }
+        if generation == generations {
+            break;
+        }
+        //不需要逐代观察时整段推进,HashLife 可以跳跃
+        let next = match record_range {
+            _ if every_generation => generation + 1,
+            Some((first, _)) if generation < first => first,
+            Some((_, end)) if generation + 1 < end => generation + 1,
+            _ => generations,
+        };
+        engine.advance(&rule, next - generation);
+        generation = next;
+    }
+    let elapsed = start.elapsed();
+
+    if let Some(path) = &options.output {
+        let mut pattern = engine.to_pattern();
+        pattern.rule = Some(rule);
+        pattern::save(path, &pattern).map_err(|e| format!("无法保存 `{}`: {}", path.display(), e))?;
+    }
+    if let Some(path) = &options.snapshot {
+        //无限宇宙只截取活细胞的包围盒,先检查尺寸,避免转换为棋盘或分配图像时直接中止程序
+        let pattern = engine.to_pattern();
+        check_image_size("截图", pattern.width.max(1), pattern.height.max(1), 1)?;
+        let board = pattern.to_board();
+        match options.snapshot_gpu {
+            true => save_snapshot_gpu(path, &board, options)?,
+            false => save_snapshot(path, &board, options)?,
+        }
+    }
+    if let (Some(path), Some(statistics)) = (&options.stats, &statistics) {
+        statistics.save(path).map_err(|e| format!("无法保存 `{}`: {}", path.display(), e))?;
+        println!("统计: {} ({} 代)", path.display(), statistics.history().len());
+    }
+    if let (Some(path), Some((writer, _, _))) = (&options.record, animation) {
+        let ((width, height), frames) = (writer.size(), writer.frame_count());
+        writer.finish().map_err(|e| format!("无法保存 `{}`: {}", path.display(), e))?;
+        println!("录制: {} ({}x{}, {} 帧)", path.display(), width, height, frames);
+    }
+
+    println!("规则: {}", rule);
+    println!("引擎: {}", options.engine);
+    if options.engine.is_unbounded() {
+        match engine.bounding_box() {
+            Some((min_x, min_y, max_x, max_y)) => {
+                println!("范围: ({}, {}) 到 ({}, {})", min_x, min_y, max_x, max_y)
+            }
+            None => println!("范围: 空"),
+        }
+    } else {
+        println!("边界: {}", options.topology);
+        println!("尺寸: {}x{}", width, height);
+    }
+    println!("代数: {}", generations);
+    println!("初始种群: {}", initial_population);
+    println!("最终种群: {}", engine.population());
+    if options.period {
+        match period {
+            Some(period) => println!("周期: {} (从第 {} 代开始)", period, period.start),
+            None => println!("周期: 未检测到"),
+        }
+    }
+    println!("耗时: {:.3}s", elapsed.as_secs_f64());
+    if elapsed.as_secs_f64() > 0.0 {
+        println!("速度: {:.1} 代/秒", generations as f64 / elapsed.as_secs_f64());
+    }
+    Ok(())
+}
+
+//在 CPU 上把整个棋盘画出来并保存为 PNG
+fn save_snapshot(path: &Path, board: &Board, options: &Options) -> Result<(), String> {
+    let raster_options = RasterOptions {
+        cell_size: snapshot_cell_size(board),
+        grid_lines: options.grid_lines,
+        colors: options.colors,
+    };
+    let (width, height) = raster_options.image_size(board);
+    let rgba = raster::rasterize(board, &raster_options);
+    snapshot::save_png(path, width, height, &rgba).map_err(|e| format!("无法保存 `{}`: {}", path.display(), e))?;
+    println!("截图: {} ({}x{})", path.display(), width, height);
+    Ok(())
+}
+
+//在离屏纹理上渲染整个棋盘并保存为 PNG,与 CPU 光栅化使用默认配色时的结果一致
+fn save_snapshot_gpu(path: &Path, board: &Board, options: &Options) -> Result<(), String> {
+    let (device, queue) = snapshot::request_device().ok_or("没有可用的 wgpu 适配器,无法截图")?;
+    let cell_size = snapshot_cell_size(board);
+    let (width, height) = (board.width() as u32 * cell_size, board.height() as u32 * cell_size);
+    let max_size = device.limits().max_texture_dimension_2d;
+    if width.max(height) > max_size {
+        return Err(format!("棋盘超过纹理尺寸上限 {},无法截图", max_size));
+    }
+
+    let rgba = snapshot::render_board(&device, &queue, board, cell_size, options.grid_lines);
+    snapshot::save_png(path, width, height, &rgba).map_err(|e| format!("无法保存 `{}`: {}", path.display(), e))?;
+    println!("截图: {} ({}x{}, wgpu)", path.display(), width, height);
+    Ok(())
+}
+
+//截图或录制的范围超过稠密棋盘的上限,或每个细胞 cell_size 像素的图像超过像素总数上限时返回错误
+fn check_image_size(action: &str, width: usize, height: usize, cell_size: usize) -> Result<(), String> {
+    pattern::check_board_size(width, height).map_err(|e| format!("无法{}: {}", action, e))?;
+    let pixels = (width * height).checked_mul(cell_size * cell_size);
+    if pixels.is_none_or(|pixels| pixels > MAX_IMAGE_PIXELS) {
+        return Err(format!(
+            "无法{}: {}x{} 个细胞、每个细胞边长 {} 像素,超过图像的像素总数上限 {}",
+            action, width, height, cell_size, MAX_IMAGE_PIXELS
+        ));
+    }
+    Ok(())
+}
+
+//每个细胞取整数个像素,使最长边接近 SNAPSHOT_LONG_SIDE
+fn snapshot_cell_size(board: &Board) -> u32 {
+    (SNAPSHOT_LONG_SIDE / board.width().max(board.height())).max(1) as u32
+}
+
+//用同样的初始棋盘先演化一遍,求出第 first 代到第 end 代(不含)所有帧活细胞的包围盒
+fn recording_bounds(
+    options: &Options,
+    initial: Initial,
+    rule: &Rule,
+    first: u64,
+    end: u64,
+) -> Result<Option<(i64, i64, i64, i64)>, String> {
+    let mut engine = options.create_engine(initial)?;
+    engine.advance(rule, first);
+    let mut bounds: Option<(i64, i64, i64, i64)> = None;
+    for generation in first..end {
+        if generation > first {
+            engine.step(rule);
+        }
+        if let Some(b) = engine.bounding_box() {
+            bounds = Some(match bounds {
+                Some(a) => (a.0.min(b.0), a.1.min(b.1), a.2.max(b.2), a.3.max(b.3)),
+                None => b,
+            });
+        }
+    }
+    Ok(bounds)
+}
@@ -0,0 +1,46 @@
+//!康威生命游戏核心库
+//!模拟部分与窗口无关,可以单独依赖和测试
+//!gpu 模块提供基于 wgpu 计算着色器的模拟,Board 作为它的 CPU 参考实现
+//!CPU 模拟通过 Engine 接口使用,Board 是固定大小的稠密棋盘,BitBoard 是它按位压缩的版本,ParallelBoard 在线程池中按行分段演化,SparseUniverse 是只保存活细胞的无限宇宙
+//!HashLife 是基于四叉树与结果缓存的无限宇宙,适合长时间演化有规律的大图样
+//!render 与 grid 模块分别提供实例化渲染与纹理全屏渲染两种绘制方式,snapshot 模块把画面渲染到离屏纹理并保存为 PNG
+//!sparkline 模块在窗口角落叠加种群曲线
+//!raster 模块不使用 wgpu,在 CPU 上把棋盘画成 RGBA 图片
+//!record 模块把每一代的画面录制为 GIF 或 APNG 动画
+//!period 模块检测棋盘何时进入循环,给出周期与位移,stats 模块记录每一代的种群、出生、死亡与包围盒
+
+pub mod bitboard;
+pub mod board;
+pub mod camera;
+pub mod engine;
+pub mod gpu;
+pub mod grid;
+pub mod hashlife;
+pub mod paint;
+pub mod parallel;
+pub mod pattern;
+pub mod period;
+pub mod raster;
+pub mod record;
+pub mod render;
+pub mod rule;
+pub mod snapshot;
+pub mod sparkline;
+pub mod sparse;
+pub mod stats;
+pub mod topology;
+pub mod view;
+
+pub use bitboard::BitBoard;
+pub use board::Board;
+pub use engine::Engine;
+pub use gpu::GpuLife;
+pub use grid::GridRenderer;
+pub use hashlife::HashLife;
+pub use parallel::ParallelBoard;
+pub use pattern::{Pattern, PatternError};
+pub use period::{Period, PeriodDetector};
+pub use rule::{Rule, RuleParseError};
+pub use sparse::SparseUniverse;
+pub use stats::{GenerationStats, Statistics};
+pub use topology::Topology;
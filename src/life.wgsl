@@ -0,0 +1,120 @@
+// 生命游戏计算着色器
+// cs_main 中每个线程计算一个细胞,从 src 读取当前代,把下一代写入 dst
+// cs_compact 把 src 中活细胞的序号紧凑地写入 live,并统计间接绘制的实例数
+
+struct Params {
+    width: u32;
+    height: u32;
+    // 第 n 位表示 n 个邻居时出生/存活
+    birth: u32;
+    survival: u32;
+    // 0: 有界 1: 环面 2: 克莱因瓶 3: 交叉帽
+    topology: u32;
+};
+
+struct Cells {
+    cells: array<u32>;
+};
+
+// 与 DrawIndexedIndirect 参数的内存布局一致
+struct DrawArgs {
+    index_count: u32;
+    instance_count: atomic<u32>;
+    first_index: u32;
+    base_vertex: i32;
+    first_instance: u32;
+};
+
+struct LiveCells {
+    indices: array<u32>;
+};
+
+[[group(0), binding(0)]] var<uniform> params: Params;
+[[group(0), binding(1)]] var<storage, read> src: Cells;
+[[group(0), binding(2)]] var<storage, read_write> dst: Cells;
+[[group(0), binding(3)]] var<storage, read_write> draw: DrawArgs;
+[[group(0), binding(4)]] var<storage, read_write> live: LiveCells;
+
+fn wrap(v: i32, n: i32) -> i32 {
+    return ((v % n) + n) % n;
+}
+
+// 与 Topology::wrap 保持一致,越界且没有对应细胞时视为死细胞
+fn cell_at(x: i32, y: i32) -> u32 {
+    let w = i32(params.width);
+    let h = i32(params.height);
+    let x_inside = x >= 0 && x < w;
+    let y_inside = y >= 0 && y < h;
+
+    var cx = x;
+    var cy = y;
+    if (!(x_inside && y_inside)) {
+        switch (params.topology) {
+            case 1u: {
+                cx = wrap(x, w);
+                cy = wrap(y, h);
+            }
+            case 2u: {
+                cx = wrap(x, w);
+                if (!y_inside) {
+                    cx = w - 1 - cx;
+                    cy = wrap(y, h);
+                }
+            }
+            case 3u: {
+                if (x_inside == y_inside) {
+                    return 0u;
+                }
+                if (x_inside) {
+                    cx = w - 1 - x;
+                    cy = wrap(y, h);
+                } else {
+                    cx = wrap(x, w);
+                    cy = h - 1 - y;
+                }
+            }
+            default: {
+                return 0u;
+            }
+        }
+    }
+    return src.cells[u32(cy * w + cx)];
+}
+
+[[stage(compute), workgroup_size(8, 8, 1)]]
+fn cs_main([[builtin(global_invocation_id)]] id: vec3<u32>) {
+    if (id.x >= params.width || id.y >= params.height) {
+        return;
+    }
+
+    let x = i32(id.x);
+    let y = i32(id.y);
+    var neighbors = 0u;
+    for (var j = -1; j <= 1; j = j + 1) {
+        for (var i = -1; i <= 1; i = i + 1) {
+            if (i != 0 || j != 0) {
+                neighbors = neighbors + cell_at(x + i, y + j);
+            }
+        }
+    }
+
+    let index = id.y * params.width + id.x;
+    var mask = params.birth;
+    if (src.cells[index] != 0u) {
+        mask = params.survival;
+    }
+    dst.cells[index] = (mask >> neighbors) & 1u;
+}
+
+[[stage(compute), workgroup_size(8, 8, 1)]]
+fn cs_compact([[builtin(global_invocation_id)]] id: vec3<u32>) {
+    if (id.x >= params.width || id.y >= params.height) {
+        return;
+    }
+
+    let index = id.y * params.width + id.x;
+    if (src.cells[index] != 0u) {
+        let slot = atomicAdd(&draw.instance_count, 1u);
+        live.indices[slot] = index;
+    }
+}
@@ -1,13 +1,11 @@
-////项目说明:
-////本项目实现使用Rust WGPU图像库编写一个康威生命游戏
-////代码逻辑参考自https://linux.cn/article-8933-1.html
-////此项目遵循GPL-v3.0进行开源
-////如果此项目涉及侵权，请联系作者或在讨论中提出
-////仅作学习探讨使用
-
-use std::thread;
+//!项目说明:
+//!本项目实现使用Rust WGPU图像库编写一个康威生命游戏
+//!代码逻辑参考自https://linux.cn/article-8933-1.html
+//!此项目遵循GPL-v3.0进行开源
+//!如果此项目涉及侵权，请联系作者或在讨论中提出
+//!仅作学习探讨使用
+
 use std::time::{Duration, Instant};
-use cgmath::{InnerSpace, Rotation3, Zero};
 use winit::{
     event::*,
     event_loop::{ControlFlow, EventLoop},
@@ -18,115 +16,80 @@ use wgpu::*;
 use wgpu::util::DeviceExt;
 use winit::window::Window;
 
-//创建顶点
-#[repr(C)]
-#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
-struct Vertex {
-    position: [f32; 3],
-    color: [f32; 3],
-}
+use conway_wgpu::hashlife::MAX_STEP_LOG2;
+use conway_wgpu::paint::{self, MAX_BRUSH_SIZE};
+use conway_wgpu::record::{RecordError, RecordOptions, Recorder};
+use conway_wgpu::render::{live_instances, visible_instances, InstanceRaw, Vertex, INDICES, VERTICES};
+use conway_wgpu::camera::Camera;
+use conway_wgpu::snapshot::{self, Offscreen};
+use conway_wgpu::sparkline::{SparklineRenderer, SPARKLINE_LENGTH};
+use conway_wgpu::view::View;
+use conway_wgpu::{pattern, Board, Engine, GpuLife, GridRenderer, Pattern, Period, PeriodDetector, Rule, Statistics};
 
-const VERTICES: &[Vertex] = &[
-    Vertex { position: [-0.05,  0.05, 0.00], color: [1.0, 1.0, 1.0] },
-    Vertex { position: [-0.05, -0.05, 0.00], color: [1.0, 1.0, 1.0] },
-    Vertex { position: [ 0.05, -0.05, 0.00], color: [1.0, 1.0, 1.0] },
-    Vertex { position: [ 0.05,  0.05, 0.00], color: [1.0, 1.0, 1.0] },
-];
-
-const INDICES: &[u16] = &[
-    0,1,2,
-    0,2,3,
-];
-
-impl Vertex {
-    fn desc<'a>() -> VertexBufferLayout<'a> {
-        VertexBufferLayout {
-            array_stride: size_of::<Vertex>() as BufferAddress,
-            step_mode: VertexStepMode::Vertex,
-            attributes: &[
-                VertexAttribute {
-                    offset: 0,
-                    shader_location: 0,
-                    format: VertexFormat::Float32x3,
-                },
-                VertexAttribute {
-                    offset: size_of::<[f32; 3]>() as BufferAddress,
-                    shader_location: 1,
-                    format: VertexFormat::Float32x3,
-                }
-            ]
-        }
-    }
-}
+mod cli;
+mod headless;
 
+const WINDOW_TITLE: &str = "WGPU Conway's Game of life";
 
+//模拟速度(代/秒),按 +/- 键在各档之间切换,超过最高一档进入最高速度模式
+const SPEEDS: &[f64] = &[0.5, 1.0, 2.0, 5.0, 10.0, 20.0, 30.0, 60.0, 120.0, 240.0];
+const DEFAULT_SPEED: usize = 3;
+//固定步长时每帧最多追赶的代数,避免卡顿后一帧内计算过多
+const MAX_STEPS_PER_FRAME: u32 = 8;
+//最高速度模式下 CPU 每帧用于演化的时间
+const MAX_SPEED_FRAME_BUDGET: Duration = Duration::from_millis(12);
+//最高速度模式下 GPU 每帧演化的代数
+const MAX_SPEED_GPU_STEPS: u32 = 64;
 
+//滚轮每格的缩放倍数
+const ZOOM_STEP: f64 = 1.1;
+//触控板像素滚动换算为滚轮格数时每格的像素数
+const PIXELS_PER_LINE: f64 = 40.0;
+//方向键每次平移的像素数
+const PAN_STEP: f64 = 40.0;
 
-////实例化缓冲区
-//定义实例化大小
-const NUM_INSTANCES_PER_ROW: u32 = 20;
-const INSTANCE_DISPLACEMENT: cgmath::Vector3<f32> = cgmath::Vector3::new(
-    NUM_INSTANCES_PER_ROW as f32 * 0.5,
-    NUM_INSTANCES_PER_ROW as f32 * 0.5,
-    0.00);
+//无限宇宙的最小缩放倍数,相对于显示整个初始棋盘
+const UNBOUNDED_MIN_ZOOM: f64 = 1e-3;
 
-struct Instance {
-    position: cgmath::Vector3<f32>,
-    rotation: cgmath::Quaternion<f32>,
-}
+//默认笔刷边长
+const DEFAULT_BRUSH_SIZE: usize = 1;
 
-impl Instance {
-    fn to_raw(&self) -> InstanceRaw {
-        InstanceRaw {
-            model: (cgmath::Matrix4::from_translation(self.position) * cgmath::Matrix4::from(self.rotation)).into(),
-        }
-    }
+//实例缓冲区的最小容量
+const MIN_INSTANCE_CAPACITY: usize = 1024;
+//窗口最长边的像素数,另一边按棋盘长宽比计算
+const WINDOW_LONG_SIDE: u32 = 500;
+
+fn create_instance_buffer(device: &Device, capacity: usize) -> Buffer {
+    device.create_buffer(&BufferDescriptor {
+        label: Some("Instance Buffer"),
+        size: (capacity * size_of::<InstanceRaw>()) as BufferAddress,
+        usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
 }
 
-#[repr(C)]
-#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
-struct InstanceRaw {
-    model: [[f32; 4]; 4],
+//GPU 模拟,计算着色器演化并直接从细胞缓冲区渲染
+struct GpuSimulation {
+    life: GpuLife,
+    render_pipeline: RenderPipeline,
 }
 
-impl InstanceRaw {
-    fn desc<'a>() -> VertexBufferLayout<'a> {
-        use std::mem;
-        VertexBufferLayout {
-            array_stride: size_of::<InstanceRaw>() as BufferAddress,
-            // 我们需要从把 Vertex 的 step mode 切换为 Instance
-            // 这样着色器只有在开始处理一次新实例化绘制时，才会接受下一份实例
-            step_mode: VertexStepMode::Instance,
-            attributes: &[
-                VertexAttribute {
-                    offset: 0,
-                    // 虽然顶点着色器现在只使用位置 0 和 1，但在后面的教程中，我们将对 Vertex 使用位置 2、3 和 4
-                    // 因此我们将从 5 号 slot 开始，以免在后面导致冲突
-                    shader_location: 5,
-                    format: VertexFormat::Float32x4,
-                },
-                // 一个 mat4 需要占用 4 个顶点 slot，因为严格来说它是 4 个vec4
-                // 我们需要为每个 vec4 定义一个 slot，并在着色器中重新组装出 mat4
-                VertexAttribute {
-                    offset: size_of::<[f32; 4]>() as BufferAddress,
-                    shader_location: 6,
-                    format: VertexFormat::Float32x4,
-                },
-                VertexAttribute {
-                    offset: size_of::<[f32; 8]>() as BufferAddress,
-                    shader_location: 7,
-                    format: VertexFormat::Float32x4,
-                },
-                VertexAttribute {
-                    offset: size_of::<[f32; 12]>() as BufferAddress,
-                    shader_location: 8,
-                    format: VertexFormat::Float32x4,
-                },
-            ],
-        }
-    }
+//左键绘制的方式,右键总是擦除
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PaintMode {
+    //把细胞设为活
+    Draw,
+    //按下时翻转光标下的细胞,拖动时沿用翻转后的状态
+    Toggle,
 }
 
+//一次按下到松开之间的笔画
+struct Stroke {
+    button: MouseButton,
+    alive: bool,
+    //上一次绘制到的细胞,下一次从这里插值
+    last: (isize, isize),
+}
 
 struct State{
     //初始化部分
@@ -136,6 +99,9 @@ struct State{
     config: SurfaceConfiguration,
     //窗口大小
     size: winit::dpi::PhysicalSize<u32>,
+    //渲染与鼠标拾取共用的坐标变换,相机 uniform 每帧从 view 更新
+    view: View,
+    camera: Camera,
     //着色器
     render_pipeline: RenderPipeline,
     //顶点
@@ -143,19 +109,75 @@ struct State{
     //索引
     index_buffer: Buffer,
     num_indices: u32,
-    //实例化
-    instances: Vec<Instance>,
+    //实例化,只包含活细胞,棋盘变化时才重新上传
     instance_buffer: Buffer,
+    //实例缓冲区能容纳的实例数,不够时才重新创建
+    instance_capacity: usize,
+    instance_count: u32,
+    //纹理全屏绘制,启用时不使用实例化渲染
+    grid: Option<GridRenderer>,
+    //上传纹理时复用的缓冲区,每个细胞一个字节
+    texels: Vec<u8>,
+    //棋盘变化后需要重新上传到实例缓冲区或纹理
+    board_dirty: bool,
     //游戏状态更新
-    board: Vec<Vec<bool>>,
-    //更新延时
+    board: Board,
+    rule: Rule,
+    //启用 GPU 模拟时 board 只用于记录尺寸与鼠标修改
+    //使用 dense 以外的 CPU 引擎时由 universe 演化,board 只记录初始尺寸与边界拓扑
+    //无限宇宙不分配与图样一样大的棋盘,board 为 1x1,只记录边界拓扑
+    universe: Option<Box<dyn Engine>>,
+    //上一次上传实例时相机看到的细胞范围,相机移动后需要重新上传
+    visible_cells: Option<((isize, isize), (isize, isize))>,
+    gpu: Option<GpuSimulation>,
+    //暂停时只有单步才会演化
+    paused: bool,
+    generation: u64,
+    //无限宇宙每次演化 2^step_log2 代
+    step_log2: u32,
+    //当前速度在 SPEEDS 中的序号,等于 SPEEDS.len() 时为最高速度模式
+    speed: usize,
+    //固定步长累加器,记录尚未演化的时间
+    accumulator: Duration,
     last_update: Instant,
     //控制设置
     mouse_position: Option<winit::dpi::PhysicalPosition<f64>>,
+    //按住中键拖动平移
+    panning: bool,
+    //鼠标绘制
+    paint_mode: PaintMode,
+    brush_size: usize,
+    stroke: Option<Stroke>,
+    //正在录制的动画,每次显示新的一代时录制一帧
+    recorder: Option<Recorder>,
+    //录制时复用的离屏纹理与最近录制的代数
+    record_target: Option<Offscreen>,
+    recorded_generation: Option<u64>,
+    //录制的动画保存路径,未指定时按停止录制时的代数命名
+    record_path: Option<std::path::PathBuf>,
+    record_options: RecordOptions,
+    //开启后 CPU 模拟且每次只演化一代时逐代检测周期,需要枚举活细胞,默认关闭
+    detect_period: bool,
+    period_detector: PeriodDetector,
+    period: Option<Period>,
+    //记录最近各代的种群,画成左下角的种群曲线;GPU 模拟时每个显示的帧读回一次
+    statistics: Statistics,
+    //种群曲线每帧复用的缓冲区
+    populations: Vec<usize>,
+    sparkline: SparklineRenderer,
+    show_sparkline: bool,
 }
 //用于处理一些操作
 impl State{
-    async fn new(window: &Window) -> Self{
+    //extent 为初始棋盘或图样所在区域的尺寸,用于设置相机
+    async fn new(
+        window: &Window,
+        board: Board,
+        extent: (usize, usize),
+        rule: Rule,
+        universe: Option<Box<dyn Engine>>,
+        options: &cli::Options,
+    ) -> Self{
         //设置窗口大小
         let size = window.inner_size();
 
@@ -189,6 +211,13 @@ impl State{
         surface.configure(&device,&config);
 
 
+    ////相机
+        let mut view = View::new(extent.0, extent.1, size.width, size.height);
+        if options.engine.is_unbounded() {
+            view.set_min_zoom(UNBOUNDED_MIN_ZOOM);
+        }
+        let camera = Camera::new(&device, &view);
+
     ////着色器设置部分
         let shader = device.create_shader_module(&ShaderModuleDescriptor {
             label: Some("Shader"),
@@ -198,7 +227,7 @@ impl State{
         let render_pipeline_layout =
             device.create_pipeline_layout(&PipelineLayoutDescriptor {
                 label: Some("Render Pipeline Layout"),
-                bind_group_layouts: &[],
+                bind_group_layouts: &[camera.bind_group_layout()],
                 push_constant_ranges: &[],
             });
 
@@ -243,6 +272,69 @@ impl State{
             multiview: None, // 5.
         });
 
+        //GPU 模拟的渲染管线,顶点着色器从存储缓冲区读取细胞状态
+        let gpu = options.gpu.then(|| {
+            let life = GpuLife::new(&device, &board, &rule);
+            let gpu_pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+                label: Some("GPU Render Pipeline Layout"),
+                bind_group_layouts: &[camera.bind_group_layout(), life.render_bind_group_layout()],
+                push_constant_ranges: &[],
+            });
+            let render_pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+                label: Some("GPU Render Pipeline"),
+                layout: Some(&gpu_pipeline_layout),
+                vertex: VertexState {
+                    module: &shader,
+                    entry_point: "vs_cells",
+                    buffers: &[Vertex::desc()],
+                },
+                fragment: Some(FragmentState {
+                    module: &shader,
+                    entry_point: "fs_main",
+                    targets: &[ColorTargetState {
+                        format: config.format,
+                        blend: Some(BlendState::REPLACE),
+                        write_mask: ColorWrites::ALL,
+                    }],
+                }),
+                primitive: PrimitiveState {
+                    topology: PrimitiveTopology::TriangleList,
+                    strip_index_format: None,
+                    front_face: FrontFace::Ccw,
+                    cull_mode: Some(Face::Back),
+                    polygon_mode: PolygonMode::Fill,
+                    unclipped_depth: false,
+                    conservative: false,
+                },
+                depth_stencil: None,
+                multisample: MultisampleState::default(),
+                multiview: None,
+            });
+            GpuSimulation { life, render_pipeline }
+        });
+
+        //纹理全屏绘制,CPU 模拟时棋盘超过纹理尺寸上限则退回实例化渲染
+        let max_texture_size = device.limits().max_texture_dimension_2d as usize;
+        let grid = match options.renderer {
+            cli::Renderer::Grid if options.engine.is_unbounded() => {
+                eprintln!("纹理全屏绘制只支持有边界的引擎,改用实例化渲染");
+                None
+            }
+            cli::Renderer::Grid if gpu.is_none() && board.width().max(board.height()) > max_texture_size => {
+                eprintln!("棋盘超过纹理尺寸上限 {},改用实例化渲染", max_texture_size);
+                None
+            }
+            cli::Renderer::Grid => Some(GridRenderer::new(
+                &device,
+                config.format,
+                &camera,
+                board.width() as u32,
+                board.height() as u32,
+                gpu.as_ref().map(|gpu| &gpu.life),
+            )),
+            cli::Renderer::Instanced => None,
+        };
+
         //顶点缓冲区
         let vertex_buffer = device.create_buffer_init(
             &util::BufferInitDescriptor {
@@ -262,49 +354,9 @@ impl State{
         );
         let num_indices = INDICES.len() as u32;
 
-        //初始化游戏
-        let mut board = initialize_board();
-        let value = &board;
-
-        //实例化绘制初始化
-        let instances = (0..NUM_INSTANCES_PER_ROW).flat_map(|y| {
-            (0..NUM_INSTANCES_PER_ROW).map(move |x| {
-                let position = cgmath::Vector3 {
-                    x: x as f32 + 0.5,
-                    y: y as f32 + 0.5,
-                    z: 0.00,
-                } - INSTANCE_DISPLACEMENT;
-
-                let mut position = position * 0.1;
-
-                if !value[x as usize][y as usize] {
-                    position = position * 1000.0;
-                }
-
-                let rotation = if position.is_zero() {
-                    // 需要这行特殊处理，这样在 (0, 0, 0) 的物体不会被缩放到 0
-                    // 因为错误的四元数会影响到缩放
-                    cgmath::Quaternion::from_axis_angle(cgmath::Vector3::unit_z(), cgmath::Deg(0.0))
-                } else {
-                    cgmath::Quaternion::from_axis_angle(position.normalize(), cgmath::Deg(0.0))
-                };
-
-                Instance {
-                    position,
-                    rotation,
-                }
-            })
-        }).collect::<Vec<_>>();
-
-        //实例化缓冲
-        let instance_data = instances.iter().map(Instance::to_raw).collect::<Vec<_>>();
-        let instance_buffer = device.create_buffer_init(
-            &util::BufferInitDescriptor {
-                label: Some("Instance Buffer"),
-                contents: bytemuck::cast_slice(&instance_data),
-                usage: BufferUsages::VERTEX,
-            }
-        );
+        //实例化缓冲,第一帧渲染前上传
+        let instance_capacity = board.population().max(MIN_INSTANCE_CAPACITY).next_power_of_two();
+        let instance_buffer = create_instance_buffer(&device, instance_capacity);
 
         //延时
         let last_update = Instant::now();
@@ -312,91 +364,571 @@ impl State{
         //初始化控制
         let mouse_position = None;
 
-        State{
+        let sparkline = SparklineRenderer::new(&device, config.format);
+
+        let mut state = State{
             surface,
             device,
             queue,
             config,
             size,
+            view,
+            camera,
             render_pipeline,
             vertex_buffer,
             index_buffer,
             num_indices,
-            instances,
             instance_buffer,
+            instance_capacity,
+            instance_count: 0,
+            grid,
+            texels: Vec::new(),
+            board_dirty: true,
             board,
+            universe,
+            visible_cells: None,
+            rule,
+            gpu,
+            paused: false,
+            generation: 0,
+            step_log2: options.step.unwrap_or(0),
+            speed: DEFAULT_SPEED,
+            accumulator: Duration::ZERO,
             last_update,
             mouse_position,
-        }
+            panning: false,
+            paint_mode: PaintMode::Draw,
+            brush_size: options.brush.unwrap_or(DEFAULT_BRUSH_SIZE),
+            stroke: None,
+            recorder: None,
+            record_target: None,
+            recorded_generation: None,
+            record_path: options.record.clone(),
+            record_options: options.record_options,
+            detect_period: options.period,
+            period_detector: PeriodDetector::new(),
+            period: None,
+            statistics: Statistics::with_history(SPARKLINE_LENGTH),
+            populations: Vec::with_capacity(SPARKLINE_LENGTH),
+            sparkline,
+            show_sparkline: true,
+        };
+        //第 0 代
+        state.observe();
+        state
     }
 
-    fn resize(&mut self, mut new_size: winit::dpi::PhysicalSize<u32>){
-        let aspect_ratio:f32 = 1.0 / 1.0; //这里使用长宽比固定窗口比例
-
-        let mut new_width = new_size.width;
-        let mut new_height = new_size.height;
-
-        if new_width as f32 / new_height as f32 > aspect_ratio {
-            // 如果宽度/高度的比例大于 1:1，则按高度调整宽度
-            new_width = (new_height as f32 * aspect_ratio) as u32;
-        } else {
-            // 如果宽度/高度的比例小于 1:1，则按宽度调整高度
-            new_height = (new_width as f32 / aspect_ratio) as u32;
-        }
-
-        // 如果新的宽高大于 0，且符合固定的宽高比
-        if new_width > 0 && new_height > 0 {
-            self.size = winit::dpi::PhysicalSize::new(new_width, new_height);
-            self.config.width = new_width;
-            self.config.height = new_height;
+    //surface 始终与窗口一样大,棋盘按 view 居中绘制
+    fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>){
+        if new_size.width > 0 && new_size.height > 0 {
+            //动画每帧的尺寸必须一致,窗口大小改变时结束录制
+            if new_size != self.size {
+                self.stop_recording();
+            }
+            self.size = new_size;
+            self.config.width = new_size.width;
+            self.config.height = new_size.height;
             self.surface.configure(&self.device, &self.config);
+            self.view.resize(new_size.width, new_size.height);
         }
     }
 
     fn input(&mut self,event: &WindowEvent) -> bool {
         match event {
             WindowEvent::CursorMoved { position, .. } => {
+                if let (true, Some(last)) = (self.panning, self.mouse_position) {
+                    self.view.pan(position.x - last.x, position.y - last.y);
+                }
                 self.mouse_position = Some(*position);
+                self.continue_stroke();
             }
-            WindowEvent::MouseInput { button, state, .. } => {
-                if *button == MouseButton::Left && *state == ElementState::Pressed {
-                    if let Some(position) = self.mouse_position {
-                        self.place_cell(position.x as f32, position.y as f32);
-                    }
-                }
+            WindowEvent::CursorLeft { .. } => {
+                self.mouse_position = None;
+                self.stroke = None;
+                self.panning = false;
+            }
+            //滚轮以光标为中心缩放
+            WindowEvent::MouseWheel { delta, .. } => {
+                let lines = match delta {
+                    MouseScrollDelta::LineDelta(_, y) => *y as f64,
+                    MouseScrollDelta::PixelDelta(position) => position.y / PIXELS_PER_LINE,
+                };
+                let (x, y) = match self.mouse_position {
+                    Some(position) => (position.x, position.y),
+                    None => (self.size.width as f64 / 2.0, self.size.height as f64 / 2.0),
+                };
+                self.view.zoom_at(x, y, ZOOM_STEP.powf(lines));
+            }
+            //中键拖动平移
+            WindowEvent::MouseInput { button: MouseButton::Middle, state, .. } => {
+                self.panning = *state == ElementState::Pressed;
+            }
+            //左键绘制,右键擦除,按住拖动时连续绘制
+            WindowEvent::MouseInput { button, state: ElementState::Pressed, .. } => match button {
+                MouseButton::Left => self.begin_stroke(*button, None),
+                MouseButton::Right => self.begin_stroke(*button, Some(false)),
+                _ => return false,
+            },
+            WindowEvent::MouseInput { button, state: ElementState::Released, .. }
+                if self.stroke.as_ref().is_some_and(|stroke| stroke.button == *button) =>
+            {
+                self.stroke = None;
             }
+            WindowEvent::KeyboardInput {
+                input: KeyboardInput {
+                    state: ElementState::Pressed,
+                    virtual_keycode: Some(key),
+                    ..
+                },
+                ..
+            } => return self.key_pressed(*key),
             _ => {}
         }
         false
     }
 
-    fn place_cell(&mut self, x: f32, y: f32) {
-        let cell_size = self.size.width as f32 / NUM_INSTANCES_PER_ROW as f32;
-        let cell_x = (x / cell_size) as usize;
-        let cell_y = (y / cell_size) as usize;
-        if cell_x < NUM_INSTANCES_PER_ROW as usize && cell_y < NUM_INSTANCES_PER_ROW as usize {
-            self.board[cell_x][cell_y] = true;
+    //处理按键,返回 true 表示按键已被处理
+    fn key_pressed(&mut self, key: VirtualKeyCode) -> bool {
+        match key {
+            //空格键暂停/继续
+            VirtualKeyCode::Space => self.paused = !self.paused,
+            //暂停时按 N 键演化一代
+            VirtualKeyCode::N => {
+                if self.paused {
+                    self.advance();
+                }
+            }
+            //R 键循环切换预设规则
+            VirtualKeyCode::R => {
+                self.rule = self.rule.next_preset();
+                self.sync_gpu_rule();
+                self.reset_period();
+                println!("规则: {}", self.rule);
+            }
+            //T 键循环切换边界拓扑
+            VirtualKeyCode::T => {
+                let topology = self.board.topology().next();
+                self.board.set_topology(topology);
+                if let Some(universe) = &mut self.universe {
+                    universe.set_topology(topology);
+                }
+                self.sync_gpu_rule();
+                self.reset_period();
+                println!("边界: {}", topology);
+            }
+            //S 键把当前棋盘保存为 RLE 文件
+            VirtualKeyCode::S => self.save_pattern(),
+            //P 键把当前画面保存为 PNG 截图
+            VirtualKeyCode::P => self.save_screenshot(),
+            //C 键开启/关闭周期检测
+            VirtualKeyCode::C => {
+                self.detect_period = !self.detect_period;
+                self.reset_period();
+                println!("周期检测: {}", if self.detect_period { "开启" } else { "关闭" });
+            }
+            //I 键显示/隐藏种群曲线
+            VirtualKeyCode::I => self.show_sparkline = !self.show_sparkline,
+            //V 键开始/停止录制动画
+            VirtualKeyCode::V => match self.recorder {
+                Some(_) => self.stop_recording(),
+                None => self.start_recording(),
+            },
+            //+/- 键调整速度
+            VirtualKeyCode::Plus | VirtualKeyCode::Equals | VirtualKeyCode::NumpadAdd => {
+                self.speed = (self.speed + 1).min(SPEEDS.len());
+            }
+            VirtualKeyCode::Minus | VirtualKeyCode::NumpadSubtract => {
+                self.speed = self.speed.saturating_sub(1);
+            }
+            //M 键切换左键绘制/翻转模式
+            VirtualKeyCode::M => {
+                self.paint_mode = match self.paint_mode {
+                    PaintMode::Draw => PaintMode::Toggle,
+                    PaintMode::Toggle => PaintMode::Draw,
+                };
+                println!("绘制模式: {}", match self.paint_mode {
+                    PaintMode::Draw => "绘制",
+                    PaintMode::Toggle => "翻转",
+                });
+            }
+            //[ 与 ] 键调整笔刷大小
+            VirtualKeyCode::LBracket => {
+                self.brush_size = self.brush_size.saturating_sub(1).max(1);
+                println!("笔刷: {}", self.brush_size);
+            }
+            VirtualKeyCode::RBracket => {
+                self.brush_size = (self.brush_size + 1).min(MAX_BRUSH_SIZE);
+                println!("笔刷: {}", self.brush_size);
+            }
+            //PageUp/PageDown 键调整无限宇宙每次演化的代数
+            VirtualKeyCode::PageUp if self.universe.is_some() => {
+                self.step_log2 = (self.step_log2 + 1).min(MAX_STEP_LOG2);
+                println!("步长: 2^{}", self.step_log2);
+            }
+            VirtualKeyCode::PageDown if self.universe.is_some() => {
+                self.step_log2 = self.step_log2.saturating_sub(1);
+                println!("步长: 2^{}", self.step_log2);
+            }
+            //方向键平移
+            VirtualKeyCode::Left => self.view.pan(PAN_STEP, 0.0),
+            VirtualKeyCode::Right => self.view.pan(-PAN_STEP, 0.0),
+            VirtualKeyCode::Up => self.view.pan(0.0, PAN_STEP),
+            VirtualKeyCode::Down => self.view.pan(0.0, -PAN_STEP),
+            //F 键让所有活细胞填满窗口,Home 或 0 键恢复显示整个棋盘
+            VirtualKeyCode::F => self.fit_pattern(),
+            VirtualKeyCode::Home | VirtualKeyCode::Key0 => self.view.reset(),
+            //G 键显示/隐藏网格线,L 键切换最近邻/线性采样,只对纹理全屏绘制有效
+            VirtualKeyCode::G => {
+                if let Some(grid) = &mut self.grid {
+                    let grid_lines = !grid.grid_lines();
+                    grid.set_grid_lines(&self.queue, grid_lines);
+                }
+            }
+            VirtualKeyCode::L => {
+                if let Some(grid) = &mut self.grid {
+                    let filter = match grid.filter() {
+                        FilterMode::Nearest => FilterMode::Linear,
+                        FilterMode::Linear => FilterMode::Nearest,
+                    };
+                    grid.set_filter(filter);
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    //光标所在的细胞,可能在棋盘之外
+    fn cursor_cell(&self) -> Option<(isize, isize)> {
+        let position = self.mouse_position?;
+        Some(self.view.screen_to_cell(position.x, position.y))
+    }
+
+    //开始一次笔画,alive 为 None 时按绘制模式决定
+    fn begin_stroke(&mut self, button: MouseButton, alive: Option<bool>) {
+        let Some(cell) = self.cursor_cell() else { return };
+        let alive = alive.unwrap_or(match self.paint_mode {
+            PaintMode::Draw => true,
+            PaintMode::Toggle => !self.cell_alive(cell),
+        });
+        self.stroke = Some(Stroke { button, alive, last: cell });
+        self.paint(cell, cell, alive);
+    }
+
+    //拖动时从上一次的细胞插值到当前细胞
+    fn continue_stroke(&mut self) {
+        let (Some(stroke), Some(cell)) = (&self.stroke, self.cursor_cell()) else { return };
+        if stroke.last == cell {
+            return;
+        }
+        let (from, alive) = (stroke.last, stroke.alive);
+        self.paint(from, cell, alive);
+        if let Some(stroke) = &mut self.stroke {
+            stroke.last = cell;
         }
     }
 
+    //GPU 模拟时 board 不是最新状态,需要从 GPU 读回
+    fn cell_alive(&self, (x, y): (isize, isize)) -> bool {
+        if let Some(universe) = &self.universe {
+            return universe.get(x as i64, y as i64);
+        }
+        if x < 0 || y < 0 {
+            return false;
+        }
+        match &self.gpu {
+            Some(gpu) => gpu.life.get_cell(&self.device, &self.queue, x as usize, y as usize),
+            None => self.board.get(x as usize, y as usize),
+        }
+    }
 
+    //用笔刷把 from 到 to 路径上的细胞设为 alive
+    fn paint(&mut self, from: (isize, isize), to: (isize, isize), alive: bool) {
+        self.reset_period();
+        self.statistics.edited();
+        if let Some(universe) = &mut self.universe {
+            for (x, y) in paint::stroke_cells(from, to, self.brush_size) {
+                universe.set(x as i64, y as i64, alive);
+            }
+            self.board_dirty = true;
+            return;
+        }
+
+        let cells = paint::stroke(from, to, self.brush_size, self.board.width(), self.board.height());
+        for &(x, y) in &cells {
+            self.board.set(x, y, alive);
+        }
+        if let Some(gpu) = &self.gpu {
+            gpu.life.set_cells(&self.queue, &cells, alive);
+        }
+        self.board_dirty |= !cells.is_empty();
+    }
+
+    //让所有活细胞居中并填满窗口,没有活细胞时显示整个棋盘
+    fn fit_pattern(&mut self) {
+        let bounds = match (&self.universe, &self.gpu) {
+            (Some(universe), _) => universe.bounding_box(),
+            (None, Some(gpu)) => Engine::bounding_box(&gpu.life.read_board(&self.device, &self.queue)),
+            (None, None) => Engine::bounding_box(&self.board),
+        };
+        match bounds {
+            Some((min_x, min_y, max_x, max_y)) => self.view.fit(
+                (min_x as f64, min_y as f64),
+                (max_x as f64 + 1.0, max_y as f64 + 1.0),
+            ),
+            None => self.view.reset(),
+        }
+    }
+
+    //保存到当前目录下以时间戳命名的 RLE 文件
+    fn save_pattern(&self) {
+        let mut pattern = match (&self.universe, &self.gpu) {
+            (Some(universe), _) => universe.to_pattern(),
+            (None, Some(gpu)) => Pattern::from_board(&gpu.life.read_board(&self.device, &self.queue)),
+            (None, None) => Pattern::from_board(&self.board),
+        };
+        pattern.rule = Some(self.rule);
+
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let path = std::path::PathBuf::from(format!("conway-{}.rle", timestamp));
+        match pattern::save(&path, &pattern) {
+            Ok(()) => println!("已保存到 {}", path.display()),
+            Err(e) => eprintln!("保存 {} 失败: {}", path.display(), e),
+        }
+    }
+
+    //把规则与边界拓扑同步到 GPU
+    fn sync_gpu_rule(&mut self) {
+        if let Some(gpu) = &mut self.gpu {
+            gpu.life.set_rule(&self.queue, &self.rule, self.board.topology());
+        }
+    }
+
+
+    //演化一代,无限宇宙演化 2^step_log2 代,GPU 模拟时单独提交一次计算
+    fn advance(&mut self) {
+        match &mut self.gpu {
+            Some(gpu) => {
+                let mut encoder = self.device.create_command_encoder(&CommandEncoderDescriptor {
+                    label: Some("Compute Encoder"),
+                });
+                gpu.life.step(&mut encoder);
+                self.queue.submit(std::iter::once(encoder.finish()));
+                self.generation += 1;
+            }
+            None => {
+                match &mut self.universe {
+                    Some(universe) => {
+                        let generations = 1 << self.step_log2;
+                        universe.advance(&self.rule, generations);
+                        self.generation += generations;
+                    }
+                    None => {
+                        self.board.step(&self.rule);
+                        self.generation += 1;
+                    }
+                }
+                self.board_dirty = true;
+            }
+        }
+        self.observe();
+    }
+
+    //记录演化后的种群,开启周期检测时检测周期,进入新的循环时输出周期
+    //只记录种群数量,不需要枚举活细胞;周期检测需要逐代的活细胞,无限宇宙一次演化多代时代数不连续,不检测
+    //GPU 模拟需要读回棋盘,都不做,种群由 sample_gpu_population 每帧读回
+    fn observe(&mut self) {
+        if self.gpu.is_some() {
+            return;
+        }
+        let engine: &dyn Engine = match &self.universe {
+            Some(universe) => universe.as_ref(),
+            None => &self.board,
+        };
+        self.statistics.observe_count(self.generation, engine.population());
+        if self.step_log2 > 0 {
+            self.reset_period();
+            return;
+        }
+        if !self.detect_period {
+            return;
+        }
+        let period = self.period_detector.observe_engine(self.generation, engine);
+        if let Some(period) = period.filter(|period| self.period.map(|p| p.start) != Some(period.start)) {
+            println!("周期: {} (从第 {} 代开始)", period, period.start);
+        }
+        self.period = period;
+    }
+
+    //棋盘、规则或拓扑被修改后之前的各代不再有效
+    fn reset_period(&mut self) {
+        self.period_detector.reset();
+        self.period = None;
+    }
+
+    //把变化后的棋盘上传到纹理或实例缓冲区
+    fn sync_board(&mut self) {
+        //其他 CPU 引擎实例化渲染时只上传相机看到的部分
+        if self.universe.is_some() && self.grid.is_none() {
+            let visible_cells = self.view.visible_cells();
+            if self.visible_cells != Some(visible_cells) {
+                self.visible_cells = Some(visible_cells);
+                self.board_dirty = true;
+            }
+        }
+        if !self.board_dirty {
+            return;
+        }
+        self.board_dirty = false;
+
+        match (&self.grid, &self.universe) {
+            //只有有边界的引擎会使用纹理,整块上传
+            (Some(grid), Some(universe)) => {
+                assert!(universe.write_texels(&mut self.texels), "纹理全屏绘制需要有边界的引擎");
+                grid.upload_texels(&self.queue, &self.texels);
+            }
+            (Some(grid), None) => grid.upload(&self.queue, &self.board),
+            (None, _) => self.sync_instances(),
+        }
+    }
+
+    //只把活细胞写入实例缓冲区,容量不足时按 2 的幂扩容
+    fn sync_instances(&mut self) {
+        let instances = match (&self.universe, self.visible_cells) {
+            (Some(universe), Some((min, max))) => {
+                visible_instances(universe.as_ref(), (min.0 as i64, min.1 as i64), (max.0 as i64, max.1 as i64))
+            }
+            _ => live_instances(&self.board),
+        };
+        if instances.len() > self.instance_capacity {
+            self.instance_capacity = instances.len().next_power_of_two();
+            self.instance_buffer = create_instance_buffer(&self.device, self.instance_capacity);
+        }
+        if !instances.is_empty() {
+            self.queue.write_buffer(&self.instance_buffer, 0, bytemuck::cast_slice(&instances));
+        }
+        self.instance_count = instances.len() as u32;
+    }
+
+    //窗口标题,显示运行状态、速度、代数、种群、规则、周期与录制状态
+    fn title(&self) -> String {
+        let status = if self.paused { "已暂停" } else { "运行中" };
+        let speed = match SPEEDS.get(self.speed) {
+            Some(speed) => format!("{} 代/秒", speed),
+            None => "最高速度".to_string(),
+        };
+        let step = match self.step_log2 {
+            0 => String::new(),
+            step_log2 => format!(" | 步长 2^{}", step_log2),
+        };
+        //GPU 模拟时只有显示种群曲线才会读回种群,不显示过时的数量
+        let population = match self.statistics.latest() {
+            Some(stats) if stats.generation == self.generation => format!(" | 种群 {}", stats.population),
+            _ => String::new(),
+        };
+        let period = match &self.period {
+            Some(period) => format!(" | {}", period),
+            None => String::new(),
+        };
+        let recording = match &self.recorder {
+            Some(recorder) => format!(" | 录制中 {} 帧", recorder.frame_count()),
+            None => String::new(),
+        };
+        format!(
+            "{} | {} | {}{} | 第 {} 代{} | {}{}{}",
+            WINDOW_TITLE, status, speed, step, self.generation, population, self.rule, period, recording
+        )
+    }
+
+    //按固定步长推进模拟,与渲染帧率无关
     fn update(&mut self){
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_update);
+        self.last_update = now;
 
+        if self.paused {
+            self.accumulator = Duration::ZERO;
+            return;
+        }
+
+        let speed = match SPEEDS.get(self.speed) {
+            Some(&speed) => speed,
+            None => {
+                //最高速度模式,每帧尽可能多地演化
+                self.accumulator = Duration::ZERO;
+                if self.gpu.is_some() {
+                    for _ in 0..MAX_SPEED_GPU_STEPS {
+                        self.advance();
+                    }
+                } else {
+                    while now.elapsed() < MAX_SPEED_FRAME_BUDGET {
+                        self.advance();
+                    }
+                }
+                return;
+            }
+        };
+
+        let step = Duration::from_secs_f64(1.0 / speed);
+        self.accumulator += elapsed;
+        let mut steps = 0;
+        while self.accumulator >= step {
+            if steps == MAX_STEPS_PER_FRAME {
+                //追赶不上时丢弃剩余时间
+                self.accumulator = Duration::ZERO;
+                break;
+            }
+            self.advance();
+            self.accumulator -= step;
+            steps += 1;
+        }
     }
 
     fn render(&mut self) -> Result<(),SurfaceError>{
         //初始化部分
         let output = self.surface.get_current_texture()?;
         let view = output.texture.create_view(&TextureViewDescriptor::default());
+        self.draw(&view);
+        //一帧中可能演化多代,只录制显示出来的画面,暂停时不重复录制
+        if self.recorder.is_some() && self.recorded_generation != Some(self.generation) {
+            self.record_frame();
+        }
+        //种群曲线只画在窗口上,不出现在截图与录制的动画中
+        if self.show_sparkline {
+            self.sample_gpu_population();
+            self.draw_sparkline(&view);
+        }
+        output.present();
+
+        Ok(())
+    }
+
+    //把当前画面绘制到 target 并提交,target 可以是窗口或离屏纹理
+    fn draw(&mut self, target: &TextureView) {
         let mut encoder = self.device.create_command_encoder(&CommandEncoderDescriptor {
             label: Some("Render Encoder"),
         });
+
+        //相机与鼠标拾取使用同一个 view
+        self.camera.update(&self.queue, &self.view);
+
+        //实例化渲染只绘制活细胞,GPU 模拟时先在计算着色器中收集活细胞作为间接绘制的实例
+        //纹理全屏绘制在 GPU 模拟时直接读取细胞缓冲区,不需要准备
+        match (&self.gpu, &self.grid) {
+            (Some(gpu), None) => gpu.life.compact(&self.queue, &mut encoder),
+            (Some(_), Some(_)) => {}
+            (None, _) => self.sync_board(),
+        }
+
         //背景调整部分
         {
             let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
                 label: Some("Render Pass"),
                 color_attachments: &[RenderPassColorAttachment {
-                    view: &view,
+                    view: target,
                     resolve_target: None,
                     ops: Operations {
                         load: LoadOp::Clear(Color {
@@ -409,119 +941,258 @@ impl State{
                 depth_stencil_attachment: None,
             });
 
-            //限制刷新频率
-            let now = Instant::now();
-            let elapsed = now.duration_since(self.last_update);
-            if elapsed >= Duration::new(0, 200_000_000) {
-                self.board = update_board(&self.board);
-                self.last_update = now;
+            if let Some(grid) = &self.grid {
+                grid.draw(&mut render_pass, &self.camera, self.gpu.as_ref().map(|gpu| &gpu.life));
             } else {
-                thread::sleep(Duration::new(0, 200_000_000) - elapsed);
+                self.draw_instances(&mut render_pass);
             }
+        }
 
-            let board_ref = &self.board;
+        // submit 方法能传入任何实现了 IntoIter 的参数
+        self.queue.submit(std::iter::once(encoder.finish()));
+    }
 
-            let instances = (0..NUM_INSTANCES_PER_ROW).flat_map(move|y| {
-                (0..NUM_INSTANCES_PER_ROW).map(move|x| {
-                    let position = cgmath::Vector3 {
-                        x: x as f32 + 0.5,
-                        y: y as f32 + 0.5,
-                        z: 0.00,
-                    } - INSTANCE_DISPLACEMENT;
+    //在已经绘制好的画面上叠加种群曲线
+    //GPU 模拟时读回 compact 统计的活细胞数作为当前一代的种群,每个显示的帧最多一次
+    //实例化渲染在 draw 中已经收集过活细胞,纹理全屏绘制需要单独收集
+    fn sample_gpu_population(&mut self) {
+        let gpu = match &self.gpu {
+            Some(gpu) => gpu,
+            None => return,
+        };
+        if self.statistics.latest().is_some_and(|stats| stats.generation == self.generation) {
+            return;
+        }
+        if self.grid.is_some() {
+            let mut encoder = self.device.create_command_encoder(&CommandEncoderDescriptor {
+                label: Some("Population Encoder"),
+            });
+            gpu.life.compact(&self.queue, &mut encoder);
+            self.queue.submit(std::iter::once(encoder.finish()));
+        }
+        let population = gpu.life.read_instance_count(&self.device, &self.queue) as usize;
+        self.statistics.observe_count(self.generation, population);
+    }
 
-                    let mut position = position * 0.1;
+    fn draw_sparkline(&mut self, target: &TextureView) {
+        self.populations.clear();
+        self.populations.extend(self.statistics.history().iter().map(|stats| stats.population));
+        self.sparkline.update(&self.queue, &self.populations, self.size.width, self.size.height);
 
-                    if !board_ref[x as usize][y as usize] {
-                        position = position * 1000.0;
-                    }
+        let mut encoder = self.device.create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("Sparkline Encoder"),
+        });
+        {
+            let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
+                label: Some("Sparkline Pass"),
+                color_attachments: &[RenderPassColorAttachment {
+                    view: target,
+                    resolve_target: None,
+                    ops: Operations {
+                        load: LoadOp::Load,
+                        store: true,
+                    },
+                }],
+                depth_stencil_attachment: None,
+            });
+            self.sparkline.draw(&mut render_pass);
+        }
+        self.queue.submit(std::iter::once(encoder.finish()));
+    }
 
-                    let rotation = if position.is_zero() {
-                        // 需要这行特殊处理，这样在 (0, 0, 0) 的物体不会被缩放到 0
-                        // 因为错误的四元数会影响到缩放
-                        cgmath::Quaternion::from_axis_angle(cgmath::Vector3::unit_z(), cgmath::Deg(0.0))
-                    } else {
-                        cgmath::Quaternion::from_axis_angle(position.normalize(), cgmath::Deg(0.0))
-                    };
+    //把当前画面渲染到与窗口一样大的离屏纹理并保存为 PNG
+    fn save_screenshot(&mut self) {
+        let (width, height) = self.view.screen_size();
+        let offscreen = Offscreen::new(&self.device, width, height, self.config.format);
+        self.draw(offscreen.view());
+        let rgba = offscreen.read_rgba(&self.device, &self.queue);
 
-                    Instance {
-                        position,
-                        rotation,
-                    }
-                })
-            }).collect::<Vec<_>>();
-
-            let instance_data = instances.iter().map(Instance::to_raw).collect::<Vec<_>>();
-            let instance_buffer = self.device.create_buffer_init(
-                &util::BufferInitDescriptor {
-                    label: Some("Instance Buffer"),
-                    contents: bytemuck::cast_slice(&instance_data),
-                    usage: BufferUsages::VERTEX,
-                }
-            );
-            self.instances = instances;
-            self.instance_buffer = instance_buffer;
+        let path = std::path::PathBuf::from(format!("conway-{}.png", self.generation));
+        match snapshot::save_png(&path, width, height, &rgba) {
+            Ok(()) => println!("截图已保存到 {}", path.display()),
+            Err(e) => eprintln!("保存 {} 失败: {}", path.display(), e),
+        }
+    }
+
+    //从当前画面开始录制
+    fn start_recording(&mut self) {
+        self.recorder = Some(Recorder::new(self.record_options));
+        self.record_frame();
+        println!("开始录制");
+    }
 
-            //着色器绑定部分
-            render_pass.set_pipeline(&self.render_pipeline);
-            //顶点设置
-            render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
-            render_pass.set_vertex_buffer(1, self.instance_buffer.slice(..));
-            //索引设置
-            render_pass.set_index_buffer(self.index_buffer.slice(..), IndexFormat::Uint16);
-            //绘制
-            render_pass.draw_indexed(0..self.num_indices, 0, 0..self.instances.len() as _);
+    //把当前画面渲染到离屏纹理并作为一帧录制
+    fn record_frame(&mut self) {
+        let (width, height) = self.view.screen_size();
+        let target = match self.record_target.take() {
+            Some(target) if target.size() == (width, height) => target,
+            _ => Offscreen::new(&self.device, width, height, self.config.format),
+        };
+        self.draw(target.view());
+        let rgba = target.read_rgba(&self.device, &self.queue);
+        self.record_target = Some(target);
+        self.recorded_generation = Some(self.generation);
+        let result = match &mut self.recorder {
+            Some(recorder) => recorder.push_rgba(width, height, rgba),
+            None => Ok(()),
+        };
+        match result {
+            Ok(()) => {}
+            //录制的帧都保存在内存中,达到上限时停止录制并保存已经录制的部分
+            Err(e @ RecordError::MemoryLimit { .. }) => {
+                eprintln!("{},停止录制", e);
+                self.stop_recording();
+            }
+            Err(e) => eprintln!("录制失败: {}", e),
         }
+    }
 
-        // submit 方法能传入任何实现了 IntoIter 的参数
-        self.queue.submit(std::iter::once(encoder.finish()));
-        output.present();
+    //停止录制并保存动画,没有在录制时什么也不做
+    fn stop_recording(&mut self) {
+        let recorder = match self.recorder.take() {
+            Some(recorder) => recorder,
+            None => return,
+        };
+        self.record_target = None;
+        self.recorded_generation = None;
+        let path = match &self.record_path {
+            Some(path) => path.clone(),
+            None => std::path::PathBuf::from(format!("conway-{}.gif", self.generation)),
+        };
+        match recorder.save(&path) {
+            Ok(()) => println!("动画已保存到 {} ({} 帧)", path.display(), recorder.frame_count()),
+            Err(e) => eprintln!("保存 {} 失败: {}", path.display(), e),
+        }
+    }
 
-        Ok(())
+    //实例化渲染
+    fn draw_instances<'a>(&'a self, render_pass: &mut RenderPass<'a>) {
+        //着色器绑定部分
+        //顶点设置
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        //索引设置
+        render_pass.set_index_buffer(self.index_buffer.slice(..), IndexFormat::Uint16);
+        //相机
+        render_pass.set_bind_group(0, self.camera.bind_group(), &[]);
+        match &self.gpu {
+            Some(gpu) => {
+                render_pass.set_pipeline(&gpu.render_pipeline);
+                render_pass.set_bind_group(1, gpu.life.render_bind_group(), &[]);
+                //绘制,实例数由 compact 写入参数缓冲区
+                render_pass.draw_indexed_indirect(gpu.life.draw_args_buffer(), 0);
+            }
+            None => {
+                render_pass.set_pipeline(&self.render_pipeline);
+                render_pass.set_vertex_buffer(1, self.instance_buffer.slice(..));
+                //绘制
+                render_pass.draw_indexed(0..self.num_indices, 0, 0..self.instance_count);
+            }
+        }
     }
 }
 
 fn main() {
-    //初始化窗口
+    //解析命令行参数
+    let options = match cli::Options::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+    if options.help {
+        println!("{}", cli::USAGE);
+        return;
+    }
+
+    //无窗口模式不创建窗口与 wgpu 设备
+    if options.command == cli::Command::Run {
+        if let Err(e) = headless::run(&options) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    //初始化游戏
+    let (initial, rule) = match options.initial() {
+        Ok(initial) => initial,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    let (width, height) = initial.size();
+
+    //dense 以外的 CPU 引擎,无限宇宙直接由活细胞创建,不分配与图样一样大的棋盘
+    let created = match options.engine {
+        cli::EngineKind::Dense => initial.into_board().map(|board| (board, None)),
+        engine if engine.is_unbounded() => options
+            .create_engine(initial)
+            .map(|universe| (Board::new(1, 1).with_topology(options.topology), Some(universe))),
+        _ => initial
+            .into_board()
+            .and_then(|board| Ok((board.clone(), Some(options.create_engine(cli::Initial::Board(board))?)))),
+    };
+    let (board, universe) = match created {
+        Ok(created) => created,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    //初始化窗口,初始比例与棋盘一致,之后可以自由调整大小,棋盘居中显示
     env_logger::init();
+    let long_side = width.max(height) as f32;
+    let window_size = winit::dpi::PhysicalSize::new(
+        (WINDOW_LONG_SIDE as f32 * width as f32 / long_side).max(1.0) as u32,
+        (WINDOW_LONG_SIDE as f32 * height as f32 / long_side).max(1.0) as u32,
+    );
     let event_loop = EventLoop::new();
     let window = WindowBuilder::new()
-        .with_title("WGPU Conway's Game of life")
-        .with_inner_size(winit::dpi::PhysicalSize::new(500,500))
-        .with_resizable(false)
+        .with_title(WINDOW_TITLE)
+        .with_inner_size(window_size)
         .build(&event_loop).unwrap();
 
-    let mut state = pollster::block_on(State::new(&window));
+    let mut state = pollster::block_on(State::new(&window, board, (width, height), rule, universe, &options));
+
+    let mut title = String::new();
 
     //窗口事件循环
     event_loop.run(move |event, _, control_flow|match event {
         Event::WindowEvent {
             ref event,
             window_id,
-        } if window_id == window.id() =>if !state.input(event) {
-            match event {
-                WindowEvent::CloseRequested | WindowEvent::KeyboardInput {
-                    input:
-                    KeyboardInput {
-                        state: ElementState::Pressed,
-                        virtual_keycode: Some(VirtualKeyCode::Escape),
-                        ..
-                    },
+        } if window_id == window.id() && !state.input(event) => match event {
+            WindowEvent::CloseRequested | WindowEvent::KeyboardInput {
+                input:
+                KeyboardInput {
+                    state: ElementState::Pressed,
+                    virtual_keycode: Some(VirtualKeyCode::Escape),
                     ..
-                } => {
-                    *control_flow = ControlFlow::Exit
                 },
-                WindowEvent::Resized(physical_size) => {
-                    state.resize(*physical_size);
-                },
-                WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
-                    // new_inner_size 是 &&mut 类型，因此需要解引用两次
-                    state.resize(**new_inner_size);
-                }
-                _ => {}
+                ..
+            } => {
+                *control_flow = ControlFlow::Exit
+            },
+            WindowEvent::Resized(physical_size) => {
+                state.resize(*physical_size);
+            },
+            WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
+                // new_inner_size 是 &&mut 类型，因此需要解引用两次
+                state.resize(**new_inner_size);
             }
+            _ => {}
         },
         Event::RedrawRequested(window_id) if window_id == window.id() => {
             state.update();
+            //标题变化时才更新,避免每帧调用系统接口
+            let new_title = state.title();
+            if new_title != title {
+                window.set_title(&new_title);
+                title = new_title;
+            }
             match state.render() {
                 Ok(_) => {}
                 // 如果发生上下文丢失，就重新配置 surface
@@ -532,6 +1203,8 @@ fn main() {
                 Err(e) => eprintln!("{:?}", e),
             }
         },
+        //退出时保存正在录制的动画
+        Event::LoopDestroyed => state.stop_recording(),
         Event::MainEventsCleared => {
             // 除非手动请求，否则 RedrawRequested 只会触发一次
             window.request_redraw();
@@ -539,58 +1212,3 @@ fn main() {
         _ => {}
     });
 }
-
-////实现细胞格子计算
-//初始化棋盘
-fn initialize_board() -> Vec<Vec<bool>> {
-    let mut board = vec![vec![false; NUM_INSTANCES_PER_ROW as usize]; NUM_INSTANCES_PER_ROW as usize];
-    for i in 0..NUM_INSTANCES_PER_ROW as usize {
-        for j in 0..NUM_INSTANCES_PER_ROW as usize {
-            board[i][j] = rand::random::<bool>(); // 随机填充
-        }
-    }
-    board
-}
-// 计算一个细胞周围活细胞的数量
-fn count_neighbors(board: &Vec<Vec<bool>>, x: usize, y: usize) -> usize {
-    let mut count = 0;
-    for i in -1..=1 {
-        for j in -1..=1 {
-            if i == 0 && j == 0 {
-                continue;
-            }
-            let nx = x as isize + i;
-            let ny = y as isize + j;
-            if nx >= 0 && ny >= 0 && nx < NUM_INSTANCES_PER_ROW as isize && ny < NUM_INSTANCES_PER_ROW as isize {
-                if board[nx as usize][ny as usize] {
-                    count += 1;
-                }
-            }
-        }
-    }
-    count
-}
-
-// 更新棋盘的状态
-fn update_board(board: &Vec<Vec<bool>>) -> Vec<Vec<bool>> {
-    let mut new_board = vec![vec![false; NUM_INSTANCES_PER_ROW as usize]; NUM_INSTANCES_PER_ROW as usize];
-
-    for i in 0..NUM_INSTANCES_PER_ROW as usize {
-        for j in 0..NUM_INSTANCES_PER_ROW as usize {
-            let neighbors = count_neighbors(board, i, j);
-            if board[i][j] {
-                // 如果当前细胞是活的
-                if neighbors == 2 || neighbors == 3 {
-                    new_board[i][j] = true; // 继续存活
-                }
-            } else {
-                // 如果当前细胞是死的
-                if neighbors == 3 {
-                    new_board[i][j] = true; // 复活
-                }
-            }
-        }
-    }
-
-    new_board
-}
@@ -0,0 +1,61 @@
+//!鼠标绘制
+//!拖动时在两次光标位置之间按直线插值,再用方形笔刷覆盖路径上的每个细胞
+
+use std::collections::HashSet;
+
+//笔刷边长的上限
+pub const MAX_BRUSH_SIZE: usize = 64;
+
+//从 from 到 to 经过的细胞,包含两端
+//使用 Bresenham 直线算法,快速拖动时相邻两次位置之间不会留下空隙
+pub fn line(from: (isize, isize), to: (isize, isize)) -> Vec<(isize, isize)> {
+    let (mut x, mut y) = from;
+    let dx = (to.0 - x).abs();
+    let dy = -(to.1 - y).abs();
+    let step_x = if x < to.0 { 1 } else { -1 };
+    let step_y = if y < to.1 { 1 } else { -1 };
+    let mut error = dx + dy;
+
+    let mut cells = Vec::with_capacity(dx.max(-dy) as usize + 1);
+    loop {
+        cells.push((x, y));
+        if (x, y) == to {
+            return cells;
+        }
+        let doubled = 2 * error;
+        if doubled >= dy {
+            error += dy;
+            x += step_x;
+        }
+        if doubled <= dx {
+            error += dx;
+            y += step_y;
+        }
+    }
+}
+
+//以 center 为中心、边长为 size 的方形笔刷覆盖的细胞,size 为偶数时中心偏向右上
+pub fn brush(center: (isize, isize), size: usize) -> impl Iterator<Item = (isize, isize)> {
+    let size = size.clamp(1, MAX_BRUSH_SIZE) as isize;
+    let start = (center.0 - (size - 1) / 2, center.1 - (size - 1) / 2);
+    (0..size).flat_map(move |dy| (0..size).map(move |dx| (start.0 + dx, start.1 + dy)))
+}
+
+//笔刷沿 from 到 to 经过的所有细胞,去掉重复的部分,用于没有边界的宇宙
+pub fn stroke_cells(from: (isize, isize), to: (isize, isize), size: usize) -> Vec<(isize, isize)> {
+    let mut seen = HashSet::new();
+    line(from, to)
+        .into_iter()
+        .flat_map(|center| brush(center, size))
+        .filter(|&cell| seen.insert(cell))
+        .collect()
+}
+
+//笔刷沿 from 到 to 经过的所有细胞,去掉重复与超出 width x height 棋盘的部分
+pub fn stroke(from: (isize, isize), to: (isize, isize), size: usize, width: usize, height: usize) -> Vec<(usize, usize)> {
+    stroke_cells(from, to, size)
+        .into_iter()
+        .filter(|&(x, y)| x >= 0 && y >= 0 && (x as usize) < width && (y as usize) < height)
+        .map(|(x, y)| (x as usize, y as usize))
+        .collect()
+}
@@ -0,0 +1,80 @@
+//!多线程的稠密棋盘
+//!包装 Board,每一代用 Board::par_step 在自己的 rayon 线程池中演化,线程数可以单独指定
+//!结果与单线程的 Board 完全一致
+
+use std::sync::Arc;
+
+use rayon::{ThreadPool, ThreadPoolBuildError, ThreadPoolBuilder};
+
+use crate::board::Board;
+use crate::engine::Engine;
+use crate::pattern::Pattern;
+use crate::rule::Rule;
+use crate::topology::Topology;
+
+#[derive(Clone)]
+pub struct ParallelBoard {
+    board: Board,
+    pool: Arc<ThreadPool>,
+}
+
+impl ParallelBoard {
+    //threads 为 0 时使用 CPU 核心数
+    pub fn new(board: Board, threads: usize) -> Result<Self, ThreadPoolBuildError> {
+        let pool = ThreadPoolBuilder::new().num_threads(threads).build()?;
+        Ok(ParallelBoard { board, pool: Arc::new(pool) })
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn into_board(self) -> Board {
+        self.board
+    }
+
+    //线程池中的线程数
+    pub fn threads(&self) -> usize {
+        self.pool.current_num_threads()
+    }
+}
+
+impl Engine for ParallelBoard {
+    fn step(&mut self, rule: &Rule) {
+        let board = &mut self.board;
+        self.pool.install(|| board.par_step(rule));
+    }
+
+    fn get(&self, x: i64, y: i64) -> bool {
+        Engine::get(&self.board, x, y)
+    }
+
+    fn set(&mut self, x: i64, y: i64, alive: bool) {
+        Engine::set(&mut self.board, x, y, alive);
+    }
+
+    fn population(&self) -> usize {
+        self.board.population()
+    }
+
+    fn live_cells(&self) -> Vec<(i64, i64)> {
+        Engine::live_cells(&self.board)
+    }
+
+    fn bounding_box(&self) -> Option<(i64, i64, i64, i64)> {
+        Engine::bounding_box(&self.board)
+    }
+
+    fn set_topology(&mut self, topology: Topology) {
+        self.board.set_topology(topology);
+    }
+
+    //保留整个棋盘的大小
+    fn to_pattern(&self) -> Pattern {
+        Pattern::from_board(&self.board)
+    }
+
+    fn write_texels(&self, texels: &mut Vec<u8>) -> bool {
+        self.board.write_texels(texels)
+    }
+}
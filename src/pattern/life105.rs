@@ -0,0 +1,101 @@
+//!Life 1.05 格式
+//!首行为 `#Life 1.05`,`#D` 为说明,`#N` 表示标准规则,`#R` 给出 S/B 记法的规则
+//!`#P x y` 开始一个图块,之后各行中 `.` 为死细胞,`*` 为活细胞
+
+use super::{Pattern, PatternError};
+use crate::rule::Rule;
+
+pub const HEADER: &str = "#Life 1.05";
+
+pub fn parse(text: &str) -> Result<Pattern, PatternError> {
+    let mut coordinates = Vec::new();
+    let mut descriptions = Vec::new();
+    let mut rule = None;
+    //当前图块左上角与当前行
+    let mut block = None;
+
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        let error = |message: String| PatternError::parse(number + 1, message);
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(directive) = line.strip_prefix('#') {
+            let mut chars = directive.chars();
+            let kind = chars.next();
+            let content = chars.as_str().trim();
+            match kind {
+                Some('D') => descriptions.push(content.to_string()),
+                Some('N') => rule = Some(Rule::CONWAY),
+                Some('R') => {
+                    let parsed = content
+                        .parse()
+                        .map_err(|e| error(format!("无效的规则 `{}`: {}", content, e)))?;
+                    rule = Some(parsed);
+                }
+                Some('P') => {
+                    let mut parts = content.split_whitespace().map(str::parse::<i64>);
+                    match (parts.next(), parts.next(), parts.next()) {
+                        (Some(Ok(x)), Some(Ok(y)), None) => block = Some((x, y)),
+                        _ => return Err(error(format!("无效的图块位置 `{}`", content))),
+                    }
+                }
+                //#Life 头部以及其他未知指令
+                _ => {}
+            }
+            continue;
+        }
+
+        //没有 #P 时图块从原点开始
+        let (block_x, block_y) = block.get_or_insert((0, 0));
+        for (x, c) in line.chars().enumerate() {
+            match c {
+                '.' => {}
+                '*' => {
+                    let x = block_x.checked_add(x as i64).ok_or(PatternError::OutOfRange)?;
+                    coordinates.push((x, *block_y));
+                }
+                _ => return Err(error(format!("无效字符 `{}`", c))),
+            }
+        }
+        *block_y = block_y.checked_add(1).ok_or(PatternError::OutOfRange)?;
+    }
+
+    let mut pattern = Pattern::try_from_coordinates(&coordinates)?;
+    if !descriptions.is_empty() {
+        pattern.name = Some(descriptions.remove(0));
+    }
+    pattern.comments = descriptions;
+    pattern.rule = rule;
+    Ok(pattern)
+}
+
+pub fn write(pattern: &Pattern) -> String {
+    let mut text = format!("{}\n", HEADER);
+    if let Some(name) = &pattern.name {
+        text.push_str(&format!("#D {}\n", name));
+    }
+    for comment in &pattern.comments {
+        text.push_str(&format!("#D {}\n", comment));
+    }
+    match pattern.rule {
+        None | Some(Rule::CONWAY) => text.push_str("#N\n"),
+        Some(rule) => {
+            let counts = |f: &dyn Fn(usize) -> bool| (0..=8).filter(|&n| f(n)).map(|n| n.to_string()).collect::<String>();
+            text.push_str(&format!("#R {}/{}\n", counts(&|n| rule.survives(n)), counts(&|n| rule.born(n))));
+        }
+    }
+
+    text.push_str("#P 0 0\n");
+    let mut rows = vec![vec!['.'; pattern.width]; pattern.height];
+    for &(x, y) in &pattern.cells {
+        rows[y][x] = '*';
+    }
+    for row in rows {
+        //行尾的死细胞可以省略,但空行至少保留一个 `.`
+        let end = row.iter().rposition(|&c| c == '*').map_or(row.len().min(1), |end| end + 1);
+        text.extend(&row[..end]);
+        text.push('\n');
+    }
+    text
+}
@@ -0,0 +1,30 @@
+//!Life 1.06 格式
+//!首行为 `#Life 1.06`,之后每行是一个活细胞的 `x y` 坐标,y 轴向下,坐标可以为负
+
+use super::{Pattern, PatternError};
+
+pub const HEADER: &str = "#Life 1.06";
+
+pub fn parse(text: &str) -> Result<Pattern, PatternError> {
+    let mut coordinates = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut parts = line.split_whitespace().map(str::parse::<i64>);
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(Ok(x)), Some(Ok(y)), None) => coordinates.push((x, y)),
+            _ => return Err(PatternError::parse(number + 1, format!("无效的坐标 `{}`", line))),
+        }
+    }
+    Pattern::try_from_coordinates(&coordinates)
+}
+
+pub fn write(pattern: &Pattern) -> String {
+    let mut text = format!("{}\n", HEADER);
+    for &(x, y) in &pattern.cells {
+        text.push_str(&format!("{} {}\n", x, y));
+    }
+    text
+}
@@ -0,0 +1,289 @@
+//!图样(pattern)文件的读写
+//!图样文件中第一行在最上方,而棋盘的 y 轴向上,放置到棋盘时会上下翻转
+
+pub mod life105;
+pub mod life106;
+pub mod plaintext;
+pub mod rle;
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::board::Board;
+use crate::rule::Rule;
+
+//图样的最大宽度与高度,防止恶意或损坏的文件用很大的数字耗尽内存
+pub const MAX_DIMENSION: usize = 1 << 20;
+//转换为稠密棋盘时的最大细胞数(宽 x 高)
+pub const MAX_BOARD_AREA: usize = 1 << 28;
+//纯文本与 Life 1.05 格式逐格写出每个细胞,保存时的最大细胞数
+pub const MAX_TEXT_AREA: usize = 1 << 24;
+
+//文件中读到的图样,cells 为活细胞坐标 (列, 行),行号从上往下数
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Pattern {
+    pub width: usize,
+    pub height: usize,
+    pub cells: Vec<(usize, usize)>,
+    pub name: Option<String>,
+    pub author: Option<String>,
+    pub comments: Vec<String>,
+    //文件中指定的规则
+    pub rule: Option<Rule>,
+}
+
+impl Pattern {
+    //以整个棋盘作为图样
+    pub fn from_board(board: &Board) -> Self {
+        let height = board.height();
+        let mut cells = Vec::with_capacity(board.population());
+        for row in 0..height {
+            for x in 0..board.width() {
+                if board.get(x, height - 1 - row) {
+                    cells.push((x, row));
+                }
+            }
+        }
+        Pattern {
+            width: board.width(),
+            height,
+            cells,
+            ..Pattern::default()
+        }
+    }
+
+    //由任意整数坐标(y 轴向下)生成图样,平移到以 (0, 0) 为左上角的包围盒中
+    pub fn from_coordinates(coordinates: &[(i64, i64)]) -> Self {
+        let min_x = coordinates.iter().map(|&(x, _)| x).min().unwrap_or(0);
+        let min_y = coordinates.iter().map(|&(_, y)| y).min().unwrap_or(0);
+        let cells = coordinates
+            .iter()
+            .map(|&(x, y)| (x.abs_diff(min_x) as usize, y.abs_diff(min_y) as usize))
+            .collect();
+        Pattern::from_cells(cells)
+    }
+
+    //与 from_coordinates 相同,但包围盒的宽或高超过 MAX_DIMENSION 时返回错误,用于解析文件中的坐标
+    pub fn try_from_coordinates(coordinates: &[(i64, i64)]) -> Result<Self, PatternError> {
+        let min_x = coordinates.iter().map(|&(x, _)| x).min().unwrap_or(0);
+        let min_y = coordinates.iter().map(|&(_, y)| y).min().unwrap_or(0);
+        let offset = |value: i64, min: i64| {
+            value
+                .checked_sub(min)
+                .and_then(|offset| usize::try_from(offset).ok())
+                .filter(|&offset| offset < MAX_DIMENSION)
+                .ok_or(PatternError::OutOfRange)
+        };
+        let cells = coordinates
+            .iter()
+            .map(|&(x, y)| Ok((offset(x, min_x)?, offset(y, min_y)?)))
+            .collect::<Result<_, PatternError>>()?;
+        Ok(Pattern::from_cells(cells))
+    }
+
+    fn from_cells(mut cells: Vec<(usize, usize)>) -> Self {
+        //与 from_board 一致,按行排列
+        cells.sort_by_key(|&(x, y)| (y, x));
+        cells.dedup();
+        Pattern {
+            width: cells.iter().map(|&(x, _)| x + 1).max().unwrap_or(0),
+            height: cells.last().map_or(0, |&(_, y)| y + 1),
+            cells,
+            ..Pattern::default()
+        }
+    }
+
+    //生成与图样同样大小的棋盘
+    pub fn to_board(&self) -> Board {
+        let mut board = Board::new(self.width.max(1), self.height.max(1));
+        self.place(&mut board);
+        board
+    }
+
+    //把图样放在棋盘中央,超出棋盘的部分会被裁掉
+    pub fn place(&self, board: &mut Board) {
+        for (x, y) in self.centered_cells(board.width(), board.height()) {
+            if x >= 0 && y >= 0 {
+                board.set(x as usize, y as usize, true);
+            }
+        }
+    }
+
+    //图样放在 width x height 棋盘中央时各个活细胞的坐标(y 轴向上),不裁掉超出棋盘的部分
+    pub fn centered_cells(&self, width: usize, height: usize) -> impl Iterator<Item = (i64, i64)> + '_ {
+        let offset_x = (width as i64 - self.width as i64) / 2;
+        let offset_y = (height as i64 - self.height as i64) / 2;
+        self.cells
+            .iter()
+            .map(move |&(x, row)| (x as i64 + offset_x, (self.height - 1 - row) as i64 + offset_y))
+    }
+}
+
+//支持的图样文件格式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    //.cells 纯文本格式
+    Plaintext,
+    //.rle 游程编码格式
+    Rle,
+    //.lif 坐标列表格式
+    Life106,
+    //.lif 图块格式
+    Life105,
+}
+
+impl Format {
+    //根据扩展名判断格式,.lif/.life 默认为 Life 1.06
+    pub fn from_path(path: &Path) -> Option<Format> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "cells" | "txt" => Some(Format::Plaintext),
+            "rle" => Some(Format::Rle),
+            "lif" | "life" => Some(Format::Life106),
+            _ => None,
+        }
+    }
+
+    //根据文件内容判断格式
+    pub fn sniff(text: &str) -> Option<Format> {
+        let first = text.lines().map(str::trim).find(|line| !line.is_empty())?;
+        if first.starts_with(life106::HEADER) {
+            return Some(Format::Life106);
+        }
+        if first.starts_with(life105::HEADER) {
+            return Some(Format::Life105);
+        }
+
+        //跳过注释后,RLE 以 `x =` 头部开始,Plaintext 只包含 `.` 与 `O`
+        let body = text
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with('!'));
+        match body {
+            Some(line) if line.starts_with('x') && line.contains('=') => Some(Format::Rle),
+            Some(line) if line.chars().all(|c| matches!(c, '.' | 'O' | '*')) => Some(Format::Plaintext),
+            None if first.starts_with('!') => Some(Format::Plaintext),
+            _ => None,
+        }
+    }
+
+    //先看扩展名,.lif/.life 两种格式共用扩展名,需要再看内容;扩展名未知时按内容判断
+    pub fn detect(path: &Path, text: &str) -> Option<Format> {
+        match Format::from_path(path) {
+            Some(Format::Life105 | Format::Life106) => Format::sniff(text).or(Some(Format::Life106)),
+            Some(format) => Some(format),
+            None => Format::sniff(text),
+        }
+    }
+
+    pub fn parse(&self, text: &str) -> Result<Pattern, PatternError> {
+        match self {
+            Format::Plaintext => plaintext::parse(text),
+            Format::Rle => rle::parse(text),
+            Format::Life106 => life106::parse(text),
+            Format::Life105 => life105::parse(text),
+        }
+    }
+
+    //纯文本与 Life 1.05 格式逐格写出包围盒中的每个细胞,细胞总数超过 MAX_TEXT_AREA 时返回错误
+    pub fn write(&self, pattern: &Pattern) -> Result<String, PatternError> {
+        let area = pattern.width.checked_mul(pattern.height);
+        if matches!(self, Format::Plaintext | Format::Life105) && area.is_none_or(|area| area > MAX_TEXT_AREA) {
+            return Err(PatternError::TooLargeForText {
+                width: pattern.width,
+                height: pattern.height,
+            });
+        }
+        Ok(match self {
+            Format::Plaintext => plaintext::write(pattern),
+            Format::Rle => rle::write(pattern),
+            Format::Life106 => life106::write(pattern),
+            Format::Life105 => life105::write(pattern),
+        })
+    }
+}
+
+//检查 width x height 的稠密棋盘能否创建,超过 MAX_DIMENSION 或 MAX_BOARD_AREA 时返回错误
+pub fn check_board_size(width: usize, height: usize) -> Result<(), PatternError> {
+    let area = width.checked_mul(height);
+    if width > MAX_DIMENSION || height > MAX_DIMENSION || area.is_none_or(|area| area > MAX_BOARD_AREA) {
+        return Err(PatternError::TooLarge { width, height });
+    }
+    Ok(())
+}
+
+//读取图样文件,按扩展名与内容自动识别格式
+pub fn load(path: &Path) -> Result<Pattern, PatternError> {
+    let text = fs::read_to_string(path)?;
+    let format = Format::detect(path, &text).ok_or_else(|| PatternError::UnknownFormat(path.display().to_string()))?;
+    format.parse(&text)
+}
+
+//保存图样文件,格式由扩展名决定
+pub fn save(path: &Path, pattern: &Pattern) -> Result<(), PatternError> {
+    let format = Format::from_path(path).ok_or_else(|| PatternError::UnknownFormat(path.display().to_string()))?;
+    fs::write(path, format.write(pattern)?)?;
+    Ok(())
+}
+
+#[derive(Debug)]
+pub enum PatternError {
+    Io(io::Error),
+    //无法识别的文件格式
+    UnknownFormat(String),
+    //第 line 行(从 1 开始)解析失败
+    Parse { line: usize, message: String },
+    //图样太大,无法转换为稠密棋盘
+    TooLarge { width: usize, height: usize },
+    //坐标的范围超过 MAX_DIMENSION
+    OutOfRange,
+    //图样太大,不能写成逐格写出的纯文本或 Life 1.05 格式
+    TooLargeForText { width: usize, height: usize },
+}
+
+impl PatternError {
+    pub(crate) fn parse(line: usize, message: impl Into<String>) -> Self {
+        PatternError::Parse {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatternError::Io(e) => write!(f, "读写文件失败: {}", e),
+            PatternError::UnknownFormat(path) => write!(f, "无法识别图样文件 `{}` 的格式", path),
+            PatternError::Parse { line, message } => write!(f, "第 {} 行: {}", line, message),
+            PatternError::TooLarge { width, height } => write!(
+                f,
+                "{}x{} 超过稠密棋盘的尺寸上限(边长 {},细胞总数 {})",
+                width, height, MAX_DIMENSION, MAX_BOARD_AREA
+            ),
+            PatternError::OutOfRange => write!(f, "坐标范围超过上限 {}", MAX_DIMENSION),
+            PatternError::TooLargeForText { width, height } => write!(
+                f,
+                "{}x{} 超过纯文本与 Life 1.05 格式的细胞总数上限 {},请改用 RLE(.rle)或 Life 1.06(.lif)格式",
+                width, height, MAX_TEXT_AREA
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PatternError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PatternError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for PatternError {
+    fn from(e: io::Error) -> Self {
+        PatternError::Io(e)
+    }
+}
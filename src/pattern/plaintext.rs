@@ -0,0 +1,56 @@
+//!Plaintext(.cells)格式
+//!`!` 开头的行为注释,`!Name:` 给出图样名称,其余每行中 `.` 为死细胞,`O` 为活细胞
+
+use super::{Pattern, PatternError};
+
+pub fn parse(text: &str) -> Result<Pattern, PatternError> {
+    let mut pattern = Pattern::default();
+    let mut rows = 0;
+
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim_end();
+        if let Some(comment) = line.strip_prefix('!') {
+            match comment.strip_prefix("Name:") {
+                Some(name) => pattern.name = Some(name.trim().to_string()),
+                None => pattern.comments.push(comment.trim().to_string()),
+            }
+            continue;
+        }
+
+        for (x, c) in line.chars().enumerate() {
+            match c {
+                '.' => {}
+                'O' | '*' => pattern.cells.push((x, rows)),
+                _ => return Err(PatternError::parse(number + 1, format!("无效字符 `{}`", c))),
+            }
+        }
+        pattern.width = pattern.width.max(line.chars().count());
+        rows += 1;
+        //末尾的空行不计入高度
+        if !line.is_empty() {
+            pattern.height = rows;
+        }
+    }
+
+    Ok(pattern)
+}
+
+pub fn write(pattern: &Pattern) -> String {
+    let mut text = String::new();
+    if let Some(name) = &pattern.name {
+        text.push_str(&format!("!Name: {}\n", name));
+    }
+    for comment in &pattern.comments {
+        text.push_str(&format!("!{}\n", comment));
+    }
+
+    let mut rows = vec![vec!['.'; pattern.width]; pattern.height];
+    for &(x, y) in &pattern.cells {
+        rows[y][x] = 'O';
+    }
+    for row in rows {
+        text.extend(row);
+        text.push('\n');
+    }
+    text
+}
@@ -0,0 +1,186 @@
+//!RLE(Run Length Encoded)格式
+//!`#N`/`#C`/`#O` 开头的行为名称、注释与作者,`x = , y = , rule =` 为头部
+//!正文中 `b` 为死细胞,`o` 为活细胞,`$` 换行,`!` 结束,前面的数字表示重复次数
+
+use super::{Pattern, PatternError, MAX_DIMENSION};
+
+//输出时每行的最大长度
+const LINE_WIDTH: usize = 70;
+//最多的活细胞数,防止很大的重复次数耗尽内存
+pub const MAX_CELLS: usize = 1 << 24;
+
+pub fn parse(text: &str) -> Result<Pattern, PatternError> {
+    let mut pattern = Pattern::default();
+    let mut lines = text.lines().enumerate();
+
+    //注释与头部
+    let mut header = None;
+    for (number, line) in lines.by_ref() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(comment) = line.strip_prefix('#') {
+            let mut chars = comment.chars();
+            let kind = chars.next();
+            let content = chars.as_str().trim().to_string();
+            match kind {
+                Some('N') => pattern.name = Some(content),
+                Some('C' | 'c') => pattern.comments.push(content),
+                Some('O') => pattern.author = Some(content),
+                //#P、#R 等位置信息对棋盘没有意义,直接忽略
+                _ => {}
+            }
+            continue;
+        }
+        header = Some((number, line));
+        break;
+    }
+
+    let (number, header) = header.ok_or_else(|| PatternError::parse(1, "缺少 `x = , y =` 头部"))?;
+    parse_header(header, &mut pattern).map_err(|message| PatternError::parse(number + 1, message))?;
+
+    //正文
+    let (mut x, mut y) = (0usize, 0usize);
+    let mut count = 0usize;
+    'body: for (number, line) in lines {
+        let error = |message: &str| PatternError::parse(number + 1, message);
+        for c in line.chars() {
+            match c {
+                '0'..='9' => {
+                    count = count
+                        .checked_mul(10)
+                        .and_then(|count| count.checked_add(c.to_digit(10).unwrap() as usize))
+                        .filter(|&count| count <= MAX_DIMENSION)
+                        .ok_or_else(|| error("重复次数过大"))?;
+                }
+                'b' | '.' => {
+                    x += count.max(1);
+                    count = 0;
+                }
+                'o' | 'A' => {
+                    let run = count.max(1);
+                    if x + run > MAX_DIMENSION {
+                        return Err(error("图样宽度超出上限"));
+                    }
+                    if pattern.cells.len() + run > MAX_CELLS {
+                        return Err(error("活细胞数量超出上限"));
+                    }
+                    pattern.cells.extend((x..x + run).map(|x| (x, y)));
+                    x += run;
+                    pattern.width = pattern.width.max(x);
+                    pattern.height = pattern.height.max(y + 1);
+                    count = 0;
+                }
+                '$' => {
+                    y += count.max(1);
+                    if y >= MAX_DIMENSION {
+                        return Err(error("图样高度超出上限"));
+                    }
+                    x = 0;
+                    count = 0;
+                }
+                '!' => break 'body,
+                c if c.is_whitespace() => {}
+                _ => return Err(PatternError::parse(number + 1, format!("无效字符 `{}`", c))),
+            }
+            //死细胞的游程可以越过活细胞的上限,但不能让坐标溢出
+            if x > MAX_DIMENSION {
+                return Err(error("图样宽度超出上限"));
+            }
+        }
+    }
+
+    Ok(pattern)
+}
+
+//解析 `x = 3, y = 3, rule = B3/S23`
+fn parse_header(header: &str, pattern: &mut Pattern) -> Result<(), String> {
+    for item in header.split(',') {
+        let (key, value) = item
+            .split_once('=')
+            .ok_or_else(|| format!("无效的头部 `{}`", item.trim()))?;
+        let value = value.trim();
+        match key.trim() {
+            "x" => pattern.width = parse_dimension(value).ok_or_else(|| format!("无效的宽度 `{}`", value))?,
+            "y" => pattern.height = parse_dimension(value).ok_or_else(|| format!("无效的高度 `{}`", value))?,
+            "rule" => {
+                //忽略 Golly 的有界网格后缀,例如 B3/S23:T100,100
+                let rule = value.split(':').next().unwrap_or(value);
+                pattern.rule = Some(rule.parse().map_err(|e| format!("无效的规则 `{}`: {}", value, e))?);
+            }
+            key => return Err(format!("未知的头部字段 `{}`", key)),
+        }
+    }
+    Ok(())
+}
+
+//不超过 MAX_DIMENSION 的宽度或高度
+fn parse_dimension(value: &str) -> Option<usize> {
+    value.parse().ok().filter(|&value| value <= MAX_DIMENSION)
+}
+
+pub fn write(pattern: &Pattern) -> String {
+    let mut text = String::new();
+    if let Some(name) = &pattern.name {
+        text.push_str(&format!("#N {}\n", name));
+    }
+    if let Some(author) = &pattern.author {
+        text.push_str(&format!("#O {}\n", author));
+    }
+    for comment in &pattern.comments {
+        text.push_str(&format!("#C {}\n", comment));
+    }
+    text.push_str(&format!("x = {}, y = {}", pattern.width, pattern.height));
+    if let Some(rule) = &pattern.rule {
+        text.push_str(&format!(", rule = {}", rule));
+    }
+    text.push('\n');
+
+    //按行排列活细胞,只写出活细胞所在的行,不需要按宽高分配整个棋盘
+    let mut cells = pattern.cells.clone();
+    cells.sort_unstable_by_key(|&(x, y)| (y, x));
+    cells.dedup();
+
+    let mut tokens = Vec::new();
+    let (mut row, mut x) = (0, 0);
+    let mut i = 0;
+    while i < cells.len() {
+        let (start, y) = cells[i];
+        if y > row {
+            tokens.push(run(y - row, '$'));
+            row = y;
+            x = 0;
+        }
+        if start > x {
+            tokens.push(run(start - x, 'b'));
+        }
+        //同一行中连续的活细胞
+        let length = cells[i..].iter().zip(start..).take_while(|&(&cell, x)| cell == (x, y)).count();
+        tokens.push(run(length, 'o'));
+        x = start + length;
+        i += length;
+    }
+    tokens.push("!".to_string());
+
+    let mut line = String::new();
+    for token in tokens {
+        if line.len() + token.len() > LINE_WIDTH {
+            text.push_str(&line);
+            text.push('\n');
+            line.clear();
+        }
+        line.push_str(&token);
+    }
+    text.push_str(&line);
+    text.push('\n');
+    text
+}
+
+fn run(length: usize, tag: char) -> String {
+    if length == 1 {
+        tag.to_string()
+    } else {
+        format!("{}{}", length, tag)
+    }
+}
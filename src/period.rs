@@ -0,0 +1,187 @@
+//!周期检测
+//!每一代把活细胞平移到包围盒左下角为原点后求哈希,与之前各代比较
+//!哈希相同时再逐个比较平移后的活细胞,确认形状确实相同,哈希碰撞不会被当作周期
+//!形状重复出现时两代之差就是周期,包围盒的移动就是位移,可以区分静物、振荡子与飞船
+//!需要逐代观察,代数不连续时重新开始
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+use crate::engine::Engine;
+
+//默认最多记住的代数,超过时忘记最早的一代,周期更长时检测不到
+pub const DEFAULT_HISTORY: usize = 1 << 16;
+//最多保存的活细胞总数,超过时同样忘记最早的一代,避免种群很大时占用过多内存
+pub const HISTORY_CELLS: usize = 1 << 24;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PeriodKind {
+    //所有细胞都已死亡
+    Extinct,
+    StillLife,
+    Oscillator,
+    Spaceship,
+}
+
+//检测到的周期
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Period {
+    //进入循环的第一代
+    pub start: u64,
+    pub period: u64,
+    //每个周期包围盒的位移,y 轴向上
+    pub dx: i64,
+    pub dy: i64,
+    pub population: usize,
+}
+
+impl Period {
+    pub fn kind(&self) -> PeriodKind {
+        if self.population == 0 {
+            PeriodKind::Extinct
+        } else if (self.dx, self.dy) != (0, 0) {
+            PeriodKind::Spaceship
+        } else if self.period == 1 {
+            PeriodKind::StillLife
+        } else {
+            PeriodKind::Oscillator
+        }
+    }
+}
+
+impl fmt::Display for Period {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind() {
+            PeriodKind::Extinct => write!(f, "全部死亡"),
+            PeriodKind::StillLife => write!(f, "静物"),
+            PeriodKind::Oscillator => write!(f, "周期 {} 的振荡子", self.period),
+            PeriodKind::Spaceship => write!(f, "周期 {} 的飞船,速度 ({}, {})/{}", self.period, self.dx, self.dy, self.period),
+        }
+    }
+}
+
+//一代的记录
+struct Seen {
+    generation: u64,
+    //包围盒左下角
+    origin: (i64, i64),
+    //平移到原点并排好序的活细胞
+    cells: Vec<(i64, i64)>,
+}
+
+pub struct PeriodDetector {
+    history: HashMap<u64, Seen>,
+    //按代数先后排列的哈希,用于忘记最早的一代
+    order: VecDeque<(u64, u64)>,
+    capacity: usize,
+    //history 中保存的活细胞总数
+    stored_cells: usize,
+    last_generation: Option<u64>,
+    //当前所在循环的第一代
+    cycle_start: Option<u64>,
+}
+
+impl Default for PeriodDetector {
+    fn default() -> Self {
+        PeriodDetector::new()
+    }
+}
+
+impl PeriodDetector {
+    pub fn new() -> Self {
+        PeriodDetector::with_history(DEFAULT_HISTORY)
+    }
+
+    //最多记住 history 代,也就是能检测到的最长周期
+    pub fn with_history(history: usize) -> Self {
+        PeriodDetector {
+            history: HashMap::new(),
+            order: VecDeque::new(),
+            capacity: history.max(1),
+            stored_cells: 0,
+            last_generation: None,
+            cycle_start: None,
+        }
+    }
+
+    //忘记之前的所有代,棋盘被修改或规则改变时调用
+    pub fn reset(&mut self) {
+        self.history.clear();
+        self.order.clear();
+        self.stored_cells = 0;
+        self.last_generation = None;
+        self.cycle_start = None;
+    }
+
+    pub fn observe_engine(&mut self, generation: u64, engine: &dyn Engine) -> Option<Period> {
+        self.observe(generation, &engine.live_cells())
+    }
+
+    //记录第 generation 代的活细胞,与之前某一代形状相同时返回周期
+    //进入循环后每一代都会返回周期,离开循环(例如飞船撞上边界)后不再返回
+    pub fn observe(&mut self, generation: u64, cells: &[(i64, i64)]) -> Option<Period> {
+        if self.last_generation.is_some_and(|last| last.checked_add(1) != Some(generation)) {
+            self.reset();
+        }
+        self.last_generation = Some(generation);
+
+        let (origin, normalized) = normalize(cells);
+        let hash = fnv1a(&normalized);
+        //保存最近一次出现的代数,下一次匹配时得到的就是最短周期
+        self.stored_cells += normalized.len();
+        let previous = self.history.insert(hash, Seen { generation, origin, cells: normalized });
+        if let Some(previous) = &previous {
+            self.stored_cells -= previous.cells.len();
+        }
+        //哈希相同但活细胞不同时是哈希碰撞,不是重复出现
+        let previous = previous.filter(|previous| previous.cells == self.history[&hash].cells);
+        self.order.push_back((hash, generation));
+        while self.order.len() > self.capacity || (self.stored_cells > HISTORY_CELLS && self.order.len() > 1) {
+            let (hash, generation) = self.order.pop_front().unwrap();
+            //同一个哈希之后又出现过时保留较新的记录
+            if self.history.get(&hash).is_some_and(|seen| seen.generation == generation) {
+                let seen = self.history.remove(&hash).unwrap();
+                self.stored_cells -= seen.cells.len();
+            }
+        }
+
+        match previous {
+            Some(seen) => {
+                let start = *self.cycle_start.get_or_insert(seen.generation);
+                Some(Period {
+                    start,
+                    period: generation - seen.generation,
+                    dx: origin.0 - seen.origin.0,
+                    dy: origin.1 - seen.origin.1,
+                    population: cells.len(),
+                })
+            }
+            None => {
+                self.cycle_start = None;
+                None
+            }
+        }
+    }
+}
+
+//包围盒左下角与平移到原点后排好序的活细胞,活细胞的顺序不影响结果
+fn normalize(cells: &[(i64, i64)]) -> ((i64, i64), Vec<(i64, i64)>) {
+    let origin = cells
+        .iter()
+        .fold(None, |origin: Option<(i64, i64)>, &(x, y)| match origin {
+            Some((min_x, min_y)) => Some((min_x.min(x), min_y.min(y))),
+            None => Some((x, y)),
+        })
+        .unwrap_or((0, 0));
+    let mut normalized: Vec<(i64, i64)> = cells.iter().map(|&(x, y)| (x - origin.0, y - origin.1)).collect();
+    normalized.sort_unstable();
+    (origin, normalized)
+}
+
+//64 位 FNV-1a 哈希,结果不随 Rust 版本变化
+fn fnv1a(cells: &[(i64, i64)]) -> u64 {
+    cells
+        .iter()
+        .flat_map(|&(x, y)| x.to_le_bytes().into_iter().chain(y.to_le_bytes()))
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3))
+}
@@ -0,0 +1,117 @@
+//!CPU 软件光栅化
+//!不使用 wgpu,直接由棋盘生成按行从上到下排列的 RGBA 像素,用于无显卡的服务器导出 PNG 与动画
+//!默认配色与网格线的位置、颜色和纹理全屏绘制 (grid.wgsl) 渲染到 sRGB 纹理的结果一致,可以作为 GPU 读回结果的基准
+
+use crate::board::Board;
+
+//网格线只在细胞边长大于该像素数时绘制,与 grid.wgsl 一致
+pub const MIN_GRID_LINE_CELL_SIZE: u32 = 5;
+
+//sRGB 颜色
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ColorScheme {
+    pub alive: [u8; 3],
+    pub dead: [u8; 3],
+    //网格线颜色,与细胞颜色在线性空间中各占一半混合
+    pub grid: [u8; 3],
+}
+
+impl Default for ColorScheme {
+    //白色活细胞、黑色死细胞,网格线为线性亮度 0.25 的灰色
+    fn default() -> Self {
+        ColorScheme {
+            alive: [255, 255, 255],
+            dead: [0, 0, 0],
+            grid: [137, 137, 137],
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RasterOptions {
+    //每个细胞的边长(像素)
+    pub cell_size: u32,
+    //在每个细胞的左边与下边画网格线
+    pub grid_lines: bool,
+    pub colors: ColorScheme,
+}
+
+impl Default for RasterOptions {
+    fn default() -> Self {
+        RasterOptions {
+            cell_size: 4,
+            grid_lines: false,
+            colors: ColorScheme::default(),
+        }
+    }
+}
+
+impl RasterOptions {
+    //棋盘生成的图片尺寸
+    pub fn image_size(&self, board: &Board) -> (u32, u32) {
+        let cell_size = self.cell_size.max(1);
+        (board.width() as u32 * cell_size, board.height() as u32 * cell_size)
+    }
+}
+
+//把整个棋盘画成 RGBA 图片,棋盘最上面一行是图片的第一行
+pub fn rasterize(board: &Board, options: &RasterOptions) -> Vec<u8> {
+    let cell_size = options.cell_size.max(1);
+    let (width, height) = options.image_size(board);
+    let grid_lines = options.grid_lines && cell_size >= MIN_GRID_LINE_CELL_SIZE;
+    let colors = &options.colors;
+    //依次为死细胞、活细胞、网格线上的死细胞、网格线上的活细胞
+    let palette = [
+        colors.dead,
+        colors.alive,
+        blend(colors.dead, colors.grid),
+        blend(colors.alive, colors.grid),
+    ]
+    .map(|[r, g, b]| [r, g, b, u8::MAX]);
+
+    let mut rgba = Vec::with_capacity((width * height * 4) as usize);
+    for py in 0..height {
+        let y = board.height() - 1 - (py / cell_size) as usize;
+        //像素中心换算到棋盘坐标后,网格线落在每个细胞最左边一列与最下面一行像素上
+        let line_row = grid_lines && py % cell_size == cell_size - 1;
+        for px in 0..width {
+            let line = line_row || (grid_lines && px % cell_size == 0);
+            let alive = board.get((px / cell_size) as usize, y);
+            rgba.extend_from_slice(&palette[line as usize * 2 + alive as usize]);
+        }
+    }
+    rgba
+}
+
+//解析 rrggbb 或 #rrggbb 形式的颜色
+pub fn parse_color(text: &str) -> Option<[u8; 3]> {
+    let hex = text.trim().trim_start_matches('#');
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+//在线性空间中各取一半,与着色器写入 sRGB 纹理时的混合结果一致
+fn blend(color: [u8; 3], grid: [u8; 3]) -> [u8; 3] {
+    std::array::from_fn(|i| linear_to_srgb((srgb_to_linear(color[i]) + srgb_to_linear(grid[i])) / 2.0))
+}
+
+fn srgb_to_linear(value: u8) -> f64 {
+    let value = value as f64 / 255.0;
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f64) -> u8 {
+    let value = if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    };
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
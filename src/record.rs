@@ -0,0 +1,329 @@
+//!录制 GIF / APNG 动画
+//!AnimationWriter 事先知道画面尺寸与帧数,每一帧写入时立即编码,不保存在内存中
+//!Recorder 用于事先不知道帧数的情况(例如窗口中的录制),把每一帧保存在内存中,结束录制时再编码
+//!Recorder 保存的像素数据有上限,达到上限后拒绝新的帧
+//!画面可以来自离屏渲染读回的 RGBA 像素,也可以由 raster 模块直接在 CPU 上由棋盘生成
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::board::Board;
+use crate::raster::{self, RasterOptions};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecordFormat {
+    Gif,
+    //动画 PNG,没有 256 色的限制
+    Apng,
+}
+
+impl RecordFormat {
+    //.gif 为 GIF,.png 与 .apng 为 APNG
+    pub fn from_path(path: &Path) -> Option<RecordFormat> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "gif" => Some(RecordFormat::Gif),
+            "png" | "apng" => Some(RecordFormat::Apng),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RecordOptions {
+    //每帧显示的毫秒数,GIF 只能精确到 10 毫秒
+    pub delay_ms: u16,
+    //由棋盘生成画面时的细胞边长、网格线与配色
+    pub raster: RasterOptions,
+}
+
+impl Default for RecordOptions {
+    fn default() -> Self {
+        RecordOptions {
+            delay_ms: 100,
+            raster: RasterOptions::default(),
+        }
+    }
+}
+
+//Recorder 默认最多在内存中保存的像素数据(字节)
+pub const DEFAULT_MEMORY_LIMIT: usize = 512 << 20;
+
+pub struct Recorder {
+    options: RecordOptions,
+    //第一帧的尺寸,之后的帧必须一样大
+    size: Option<(u32, u32)>,
+    frames: Vec<Vec<u8>>,
+    //所有帧的像素数据合计不超过的字节数
+    memory_limit: usize,
+}
+
+impl Recorder {
+    pub fn new(options: RecordOptions) -> Self {
+        Recorder {
+            options,
+            size: None,
+            frames: Vec::new(),
+            memory_limit: DEFAULT_MEMORY_LIMIT,
+        }
+    }
+
+    //保存的像素数据上限(字节)
+    pub fn set_memory_limit(&mut self, bytes: usize) {
+        self.memory_limit = bytes;
+    }
+
+    pub fn memory_limit(&self) -> usize {
+        self.memory_limit
+    }
+
+    pub fn options(&self) -> &RecordOptions {
+        &self.options
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    //帧的尺寸,还没有帧时为 None
+    pub fn size(&self) -> Option<(u32, u32)> {
+        self.size
+    }
+
+    //在 CPU 上由棋盘生成一帧,棋盘最上面一行是画面的第一行
+    pub fn push_board(&mut self, board: &Board) -> Result<(), RecordError> {
+        let (width, height) = self.options.raster.image_size(board);
+        let rgba = raster::rasterize(board, &self.options.raster);
+        self.push_rgba(width, height, rgba)
+    }
+
+    //添加一帧按行从上到下排列的 RGBA 像素,例如离屏渲染读回的画面
+    pub fn push_rgba(&mut self, width: u32, height: u32, rgba: Vec<u8>) -> Result<(), RecordError> {
+        assert_eq!(rgba.len(), (width * height * 4) as usize, "RGBA 像素数量与尺寸不一致");
+        let expected = *self.size.get_or_insert((width, height));
+        if expected != (width, height) {
+            return Err(RecordError::SizeMismatch { expected, actual: (width, height) });
+        }
+        if (self.frames.len() + 1) * rgba.len() > self.memory_limit {
+            return Err(RecordError::MemoryLimit { frames: self.frames.len() });
+        }
+        self.frames.push(rgba);
+        Ok(())
+    }
+
+    //保存动画,格式由扩展名决定
+    pub fn save(&self, path: &Path) -> Result<(), RecordError> {
+        let format = RecordFormat::from_path(path).ok_or_else(|| RecordError::UnknownFormat(path.display().to_string()))?;
+        self.encode(BufWriter::new(File::create(path)?), format)
+    }
+
+    pub fn encode<W: Write>(&self, writer: W, format: RecordFormat) -> Result<(), RecordError> {
+        let (width, height) = self.size.ok_or(RecordError::NoFrames)?;
+        let mut animation = AnimationWriter::new(writer, format, width, height, self.frames.len() as u32, self.options)?;
+        for rgba in &self.frames {
+            animation.write_rgba(width, height, rgba)?;
+        }
+        animation.finish()
+    }
+}
+
+//逐帧编码的动画
+pub struct AnimationWriter<W: Write> {
+    encoder: AnimationEncoder<W>,
+    options: RecordOptions,
+    size: (u32, u32),
+    //创建时声明的帧数
+    frames: usize,
+    frame_count: usize,
+}
+
+enum AnimationEncoder<W: Write> {
+    Gif(gif::Encoder<W>),
+    Apng(png::Writer<W>),
+}
+
+impl AnimationWriter<BufWriter<File>> {
+    //创建动画文件,格式由扩展名决定
+    pub fn create(path: &Path, width: u32, height: u32, frames: u32, options: RecordOptions) -> Result<Self, RecordError> {
+        let format = RecordFormat::from_path(path).ok_or_else(|| RecordError::UnknownFormat(path.display().to_string()))?;
+        AnimationWriter::new(BufWriter::new(File::create(path)?), format, width, height, frames, options)
+    }
+}
+
+impl<W: Write> AnimationWriter<W> {
+    //每帧 width x height 像素,之后必须正好写入 frames 帧,APNG 需要事先把帧数写入文件头
+    pub fn new(
+        writer: W,
+        format: RecordFormat,
+        width: u32,
+        height: u32,
+        frames: u32,
+        options: RecordOptions,
+    ) -> Result<Self, RecordError> {
+        let encoder = match format {
+            RecordFormat::Gif => {
+                let (width, height) = match (u16::try_from(width), u16::try_from(height)) {
+                    (Ok(width), Ok(height)) => (width, height),
+                    _ => return Err(RecordError::TooLarge { width, height }),
+                };
+                let mut encoder = gif::Encoder::new(writer, width, height, &[]).map_err(RecordError::encoding)?;
+                encoder.set_repeat(gif::Repeat::Infinite).map_err(RecordError::encoding)?;
+                AnimationEncoder::Gif(encoder)
+            }
+            RecordFormat::Apng => {
+                let mut encoder = png::Encoder::new(writer, width, height);
+                encoder.set_color(png::ColorType::Rgba);
+                encoder.set_depth(png::BitDepth::Eight);
+                //0 表示无限循环
+                encoder.set_animated(frames, 0).map_err(RecordError::encoding)?;
+                encoder.set_frame_delay(options.delay_ms, 1000).map_err(RecordError::encoding)?;
+                AnimationEncoder::Apng(encoder.write_header().map_err(RecordError::encoding)?)
+            }
+        };
+        Ok(AnimationWriter { encoder, options, size: (width, height), frames: frames as usize, frame_count: 0 })
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    //已经写入的帧数
+    pub fn frame_count(&self) -> usize {
+        self.frame_count
+    }
+
+    //在 CPU 上由棋盘生成一帧并写入
+    pub fn write_board(&mut self, board: &Board) -> Result<(), RecordError> {
+        let (width, height) = self.options.raster.image_size(board);
+        if (width, height) != self.size {
+            return Err(RecordError::SizeMismatch { expected: self.size, actual: (width, height) });
+        }
+        self.write_rgba(width, height, &raster::rasterize(board, &self.options.raster))
+    }
+
+    //写入一帧按行从上到下排列的 RGBA 像素
+    pub fn write_rgba(&mut self, width: u32, height: u32, rgba: &[u8]) -> Result<(), RecordError> {
+        assert_eq!(rgba.len(), (width * height * 4) as usize, "RGBA 像素数量与尺寸不一致");
+        if (width, height) != self.size {
+            return Err(RecordError::SizeMismatch { expected: self.size, actual: (width, height) });
+        }
+        if self.frame_count == self.frames {
+            return Err(RecordError::FrameCount { expected: self.frames, actual: self.frame_count + 1 });
+        }
+        match &mut self.encoder {
+            AnimationEncoder::Gif(encoder) => {
+                //尺寸已经在创建时检查过
+                let (width, height) = (width as u16, height as u16);
+                //颜色不超过 256 种时直接使用这些颜色,否则量化
+                let mut frame = match palette(rgba) {
+                    Some((palette, indices)) => gif::Frame::from_palette_pixels(width, height, indices, palette, None),
+                    None => gif::Frame::from_rgba_speed(width, height, &mut rgba.to_vec(), GIF_QUANTIZE_SPEED),
+                };
+                frame.delay = self.options.delay_ms.div_ceil(10);
+                encoder.write_frame(&frame).map_err(RecordError::encoding)?;
+            }
+            AnimationEncoder::Apng(writer) => writer.write_image_data(rgba).map_err(RecordError::encoding)?,
+        }
+        self.frame_count += 1;
+        Ok(())
+    }
+
+    //写入文件结尾并刷新缓冲区
+    pub fn finish(self) -> Result<(), RecordError> {
+        if self.frame_count != self.frames {
+            return Err(RecordError::FrameCount { expected: self.frames, actual: self.frame_count });
+        }
+        match self.encoder {
+            AnimationEncoder::Gif(encoder) => encoder.into_inner()?.flush()?,
+            AnimationEncoder::Apng(writer) => writer.finish().map_err(RecordError::encoding)?,
+        }
+        Ok(())
+    }
+}
+
+//量化颜色时的速度,1 最慢质量最好,30 最快
+const GIF_QUANTIZE_SPEED: i32 = 10;
+
+//颜色不超过 256 种时返回调色板与每个像素的序号
+fn palette(rgba: &[u8]) -> Option<(Vec<u8>, Vec<u8>)> {
+    let mut colors: Vec<[u8; 3]> = Vec::new();
+    let mut indices = Vec::with_capacity(rgba.len() / 4);
+    for pixel in rgba.chunks(4) {
+        let color = [pixel[0], pixel[1], pixel[2]];
+        //相邻像素通常颜色相同,先和上一个像素比较
+        let index = match indices.last() {
+            Some(&last) if colors[last as usize] == color => last,
+            _ => match colors.iter().position(|&c| c == color) {
+                Some(index) => index as u8,
+                None if colors.len() < 256 => {
+                    colors.push(color);
+                    (colors.len() - 1) as u8
+                }
+                None => return None,
+            },
+        };
+        indices.push(index);
+    }
+    Some((colors.concat(), indices))
+}
+
+#[derive(Debug)]
+pub enum RecordError {
+    Io(io::Error),
+    //编码 GIF 或 APNG 失败
+    Encoding(String),
+    //无法识别的文件格式
+    UnknownFormat(String),
+    //还没有录制任何帧
+    NoFrames,
+    //帧的尺寸与第一帧不一致
+    SizeMismatch { expected: (u32, u32), actual: (u32, u32) },
+    //GIF 的边长不能超过 65535
+    TooLarge { width: u32, height: u32 },
+    //逐帧编码时写入的帧数与创建时声明的不一致
+    FrameCount { expected: usize, actual: usize },
+    //Recorder 保存的帧达到内存上限,已经保存了 frames 帧
+    MemoryLimit { frames: usize },
+}
+
+impl RecordError {
+    fn encoding(error: impl fmt::Display) -> Self {
+        RecordError::Encoding(error.to_string())
+    }
+}
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecordError::Io(e) => write!(f, "读写文件失败: {}", e),
+            RecordError::Encoding(message) => write!(f, "编码动画失败: {}", message),
+            RecordError::UnknownFormat(path) => write!(f, "无法识别动画文件 `{}` 的格式,可选 .gif、.png、.apng", path),
+            RecordError::NoFrames => write!(f, "没有录制任何帧"),
+            RecordError::SizeMismatch { expected, actual } => write!(
+                f,
+                "帧的尺寸 {}x{} 与第一帧 {}x{} 不一致",
+                actual.0, actual.1, expected.0, expected.1
+            ),
+            RecordError::TooLarge { width, height } => write!(f, "画面 {}x{} 超过 GIF 的尺寸上限 65535", width, height),
+            RecordError::FrameCount { expected, actual } => write!(f, "写入了 {} 帧,与声明的 {} 帧不一致", actual, expected),
+            RecordError::MemoryLimit { frames } => write!(f, "录制了 {} 帧后达到内存上限", frames),
+        }
+    }
+}
+
+impl std::error::Error for RecordError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RecordError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for RecordError {
+    fn from(e: io::Error) -> Self {
+        RecordError::Io(e)
+    }
+}
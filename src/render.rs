@@ -0,0 +1,159 @@
+//!实例化渲染所需的顶点与实例数据
+//!每个活细胞是一个实例,共用同一个正方形的顶点与索引
+
+use cgmath::{InnerSpace, Rotation3, Zero};
+use wgpu::*;
+
+use crate::board::Board;
+use crate::engine::Engine;
+
+//创建顶点
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct Vertex {
+    pub position: [f32; 3],
+    pub color: [f32; 3],
+}
+
+pub const VERTICES: &[Vertex] = &[
+    Vertex { position: [-0.5,  0.5, 0.00], color: [1.0, 1.0, 1.0] },
+    Vertex { position: [-0.5, -0.5, 0.00], color: [1.0, 1.0, 1.0] },
+    Vertex { position: [ 0.5, -0.5, 0.00], color: [1.0, 1.0, 1.0] },
+    Vertex { position: [ 0.5,  0.5, 0.00], color: [1.0, 1.0, 1.0] },
+];
+
+pub const INDICES: &[u16] = &[
+    0,1,2,
+    0,2,3,
+];
+
+impl Vertex {
+    pub fn desc<'a>() -> VertexBufferLayout<'a> {
+        VertexBufferLayout {
+            array_stride: size_of::<Vertex>() as BufferAddress,
+            step_mode: VertexStepMode::Vertex,
+            attributes: &[
+                VertexAttribute {
+                    offset: 0,
+                    shader_location: 0,
+                    format: VertexFormat::Float32x3,
+                },
+                VertexAttribute {
+                    offset: size_of::<[f32; 3]>() as BufferAddress,
+                    shader_location: 1,
+                    format: VertexFormat::Float32x3,
+                }
+            ]
+        }
+    }
+}
+
+pub struct Instance {
+    pub position: cgmath::Vector3<f32>,
+    pub rotation: cgmath::Quaternion<f32>,
+    pub scale: cgmath::Vector2<f32>,
+}
+
+impl Instance {
+    pub fn to_raw(&self) -> InstanceRaw {
+        InstanceRaw {
+            model: (cgmath::Matrix4::from_translation(self.position)
+                * cgmath::Matrix4::from(self.rotation)
+                * cgmath::Matrix4::from_nonuniform_scale(self.scale.x, self.scale.y, 1.0)).into(),
+        }
+    }
+}
+
+//单个活细胞的实例,把单位正方形移到棋盘坐标 (x, y) 处的细胞上,再由相机矩阵变换到屏幕
+pub fn cell_instance(x: usize, y: usize) -> Instance {
+    instance_at(x as f32, y as f32)
+}
+
+//左下角在 (x, y) 的单位正方形,无限宇宙中的坐标可以为负
+fn instance_at(x: f32, y: f32) -> Instance {
+    let scale = cgmath::Vector2::new(1.0, 1.0);
+    let position = cgmath::Vector3 {
+        x: x + 0.5,
+        y: y + 0.5,
+        z: 0.00,
+    };
+
+    let rotation = if position.is_zero() {
+        // 需要这行特殊处理，这样在 (0, 0, 0) 的物体不会被缩放到 0
+        // 因为错误的四元数会影响到缩放
+        cgmath::Quaternion::from_axis_angle(cgmath::Vector3::unit_z(), cgmath::Deg(0.0))
+    } else {
+        cgmath::Quaternion::from_axis_angle(position.normalize(), cgmath::Deg(0.0))
+    };
+
+    Instance {
+        position,
+        rotation,
+        scale,
+    }
+}
+
+//只为活细胞生成实例,实例数等于种群数量
+pub fn live_instances(board: &Board) -> Vec<InstanceRaw> {
+    let width = board.width();
+    let mut instances = Vec::with_capacity(board.population());
+    for (i, &alive) in board.cells().iter().enumerate() {
+        if alive {
+            instances.push(cell_instance(i % width, i / width).to_raw());
+        }
+    }
+    instances
+}
+
+//引擎中 min 到 max(包含两端)范围内活细胞的实例,只绘制相机看到的部分
+//坐标以 f32 传给着色器,绝对值超过 2^24 后会失去精度
+pub fn visible_instances(engine: &dyn Engine, min: (i64, i64), max: (i64, i64)) -> Vec<InstanceRaw> {
+    engine
+        .live_cells_in(min, max)
+        .into_iter()
+        .map(|(x, y)| instance_at(x as f32, y as f32).to_raw())
+        .collect()
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct InstanceRaw {
+    pub model: [[f32; 4]; 4],
+}
+
+impl InstanceRaw {
+    pub fn desc<'a>() -> VertexBufferLayout<'a> {
+        VertexBufferLayout {
+            array_stride: size_of::<InstanceRaw>() as BufferAddress,
+            // 我们需要从把 Vertex 的 step mode 切换为 Instance
+            // 这样着色器只有在开始处理一次新实例化绘制时，才会接受下一份实例
+            step_mode: VertexStepMode::Instance,
+            attributes: &[
+                VertexAttribute {
+                    offset: 0,
+                    // 虽然顶点着色器现在只使用位置 0 和 1，但在后面的教程中，我们将对 Vertex 使用位置 2、3 和 4
+                    // 因此我们将从 5 号 slot 开始，以免在后面导致冲突
+                    shader_location: 5,
+                    format: VertexFormat::Float32x4,
+                },
+                // 一个 mat4 需要占用 4 个顶点 slot，因为严格来说它是 4 个vec4
+                // 我们需要为每个 vec4 定义一个 slot，并在着色器中重新组装出 mat4
+                VertexAttribute {
+                    offset: size_of::<[f32; 4]>() as BufferAddress,
+                    shader_location: 6,
+                    format: VertexFormat::Float32x4,
+                },
+                VertexAttribute {
+                    offset: size_of::<[f32; 8]>() as BufferAddress,
+                    shader_location: 7,
+                    format: VertexFormat::Float32x4,
+                },
+                VertexAttribute {
+                    offset: size_of::<[f32; 12]>() as BufferAddress,
+                    shader_location: 8,
+                    format: VertexFormat::Float32x4,
+                },
+            ],
+        }
+    }
+}
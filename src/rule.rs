@@ -0,0 +1,204 @@
+//!类生命(Life-like)规则
+//!支持标准的 B/S 记法(如 `B36/S23`)以及旧式的 S/B 记法(如 `23/3`)
+
+use std::fmt;
+use std::str::FromStr;
+
+//规则使用位掩码保存,第 n 位表示 n 个邻居时出生/存活
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Rule {
+    birth: u16,
+    survival: u16,
+}
+
+impl Rule {
+    //康威生命游戏 B3/S23
+    pub const CONWAY: Rule = Rule { birth: 1 << 3, survival: 1 << 2 | 1 << 3 };
+    //HighLife B36/S23
+    pub const HIGHLIFE: Rule = Rule { birth: 1 << 3 | 1 << 6, survival: 1 << 2 | 1 << 3 };
+    //Seeds B2/S
+    pub const SEEDS: Rule = Rule { birth: 1 << 2, survival: 0 };
+    //Day & Night B3678/S34678
+    pub const DAY_AND_NIGHT: Rule = Rule {
+        birth: 1 << 3 | 1 << 6 | 1 << 7 | 1 << 8,
+        survival: 1 << 3 | 1 << 4 | 1 << 6 | 1 << 7 | 1 << 8,
+    };
+
+    //运行时可以循环切换的预设规则
+    pub const PRESETS: &'static [Rule] = &[Rule::CONWAY, Rule::HIGHLIFE, Rule::SEEDS, Rule::DAY_AND_NIGHT];
+
+    //由出生、存活的邻居数量创建规则,超过 8 的数量会被忽略
+    pub fn new(birth: &[u8], survival: &[u8]) -> Self {
+        let mask = |counts: &[u8]| counts.iter().filter(|&&n| n <= 8).fold(0u16, |mask, &n| mask | 1 << n);
+        Rule {
+            birth: mask(birth),
+            survival: mask(survival),
+        }
+    }
+
+    //出生条件的位掩码,第 n 位表示 n 个邻居时出生
+    pub fn birth_mask(&self) -> u16 {
+        self.birth
+    }
+
+    //存活条件的位掩码,第 n 位表示 n 个邻居时存活
+    pub fn survival_mask(&self) -> u16 {
+        self.survival
+    }
+
+    //死细胞在 neighbors 个邻居时是否出生
+    pub fn born(&self, neighbors: usize) -> bool {
+        neighbors <= 8 && self.birth & (1 << neighbors) != 0
+    }
+
+    //活细胞在 neighbors 个邻居时是否存活
+    pub fn survives(&self, neighbors: usize) -> bool {
+        neighbors <= 8 && self.survival & (1 << neighbors) != 0
+    }
+
+    //计算细胞的下一代状态
+    pub fn next_state(&self, alive: bool, neighbors: usize) -> bool {
+        if alive {
+            self.survives(neighbors)
+        } else {
+            self.born(neighbors)
+        }
+    }
+
+    //预设列表中的下一个规则,不在列表中时回到第一个
+    pub fn next_preset(&self) -> Rule {
+        let index = Rule::PRESETS.iter().position(|rule| rule == self);
+        match index {
+            Some(i) => Rule::PRESETS[(i + 1) % Rule::PRESETS.len()],
+            None => Rule::PRESETS[0],
+        }
+    }
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Rule::CONWAY
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "B")?;
+        for n in (0..=8).filter(|&n| self.born(n)) {
+            write!(f, "{}", n)?;
+        }
+        write!(f, "/S")?;
+        for n in (0..=8).filter(|&n| self.survives(n)) {
+            write!(f, "{}", n)?;
+        }
+        Ok(())
+    }
+}
+
+//规则解析错误
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RuleParseError {
+    //空字符串
+    Empty,
+    //无法识别的字符
+    InvalidChar(char),
+    //邻居数量只能是 0 到 8
+    InvalidCount(char),
+    //B 或 S 部分重复出现
+    DuplicateSection(char),
+    //缺少 B 或 S 部分
+    MissingSection(char),
+    //数字出现在 B/S 标记之前
+    MissingPrefix,
+    //S/B 记法缺少 `/`
+    MissingSlash,
+}
+
+impl fmt::Display for RuleParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuleParseError::Empty => write!(f, "规则为空"),
+            RuleParseError::InvalidChar(c) => write!(f, "规则中包含无效字符 `{}`", c),
+            RuleParseError::InvalidCount(c) => write!(f, "邻居数量 `{}` 超出范围,只能是 0 到 8", c),
+            RuleParseError::DuplicateSection(c) => write!(f, "规则中 `{}` 部分重复出现", c),
+            RuleParseError::MissingSection(c) => write!(f, "规则缺少 `{}` 部分", c),
+            RuleParseError::MissingPrefix => write!(f, "邻居数量前缺少 `B` 或 `S` 标记"),
+            RuleParseError::MissingSlash => write!(f, "S/B 记法需要用 `/` 分隔,例如 `23/3`"),
+        }
+    }
+}
+
+impl std::error::Error for RuleParseError {}
+
+impl FromStr for Rule {
+    type Err = RuleParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(RuleParseError::Empty);
+        }
+
+        if s.chars().any(|c| c.is_ascii_alphabetic()) {
+            parse_bs(s)
+        } else {
+            parse_sb(s)
+        }
+    }
+}
+
+//B3/S23 记法,B 与 S 的顺序不限,`/` 可以省略
+fn parse_bs(s: &str) -> Result<Rule, RuleParseError> {
+    let mut birth = None;
+    let mut survival = None;
+    let mut current: Option<&mut u16> = None;
+
+    for c in s.chars() {
+        match c.to_ascii_uppercase() {
+            section @ ('B' | 'S') => {
+                let slot = if section == 'B' { &mut birth } else { &mut survival };
+                if slot.is_some() {
+                    return Err(RuleParseError::DuplicateSection(section));
+                }
+                current = Some(slot.insert(0));
+            }
+            '/' => {}
+            digit @ '0'..='9' => {
+                let mask = current.as_deref_mut().ok_or(RuleParseError::MissingPrefix)?;
+                *mask |= 1 << count_from_digit(digit)?;
+            }
+            _ => return Err(RuleParseError::InvalidChar(c)),
+        }
+    }
+
+    Ok(Rule {
+        birth: birth.ok_or(RuleParseError::MissingSection('B'))?,
+        survival: survival.ok_or(RuleParseError::MissingSection('S'))?,
+    })
+}
+
+//23/3 记法,斜杠前为存活条件,斜杠后为出生条件
+fn parse_sb(s: &str) -> Result<Rule, RuleParseError> {
+    let (survival, birth) = s.split_once('/').ok_or(RuleParseError::MissingSlash)?;
+    Ok(Rule {
+        birth: parse_counts(birth)?,
+        survival: parse_counts(survival)?,
+    })
+}
+
+fn parse_counts(s: &str) -> Result<u16, RuleParseError> {
+    s.trim().chars().try_fold(0u16, |mask, c| {
+        if c.is_ascii_digit() {
+            Ok(mask | 1 << count_from_digit(c)?)
+        } else {
+            Err(RuleParseError::InvalidChar(c))
+        }
+    })
+}
+
+fn count_from_digit(c: char) -> Result<u16, RuleParseError> {
+    match c.to_digit(10) {
+        Some(n) if n <= 8 => Ok(n as u16),
+        _ => Err(RuleParseError::InvalidCount(c)),
+    }
+}
@@ -1,4 +1,12 @@
 // 顶点着色器
+// 实例矩阵把单位正方形放到棋盘坐标,相机矩阵再把棋盘坐标变换到裁剪空间
+
+struct Camera {
+    view_proj: mat4x4<f32>;
+    inverse_view_proj: mat4x4<f32>;
+};
+
+[[group(0), binding(0)]] var<uniform> camera: Camera;
 
 struct VertexInput {
     [[location(0)]] position: vec3<f32>;
@@ -30,7 +38,7 @@ fn vs_main(
         );
     var out: VertexOutput;
     out.color = model.color;
-    out.clip_position = model_matrix * vec4<f32>(model.position, 1.0);
+    out.clip_position = camera.view_proj * model_matrix * vec4<f32>(model.position, 1.0);
     return out;
 }
 
@@ -39,4 +47,35 @@ fn vs_main(
 [[stage(fragment)]]
 fn fs_main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
     return vec4<f32>(in.color, 1.0);
-}
\ No newline at end of file
+}
+// GPU 模拟时的顶点着色器
+// 每个实例对应计算着色器紧凑排列出的一个活细胞,按细胞序号计算位置
+
+struct Params {
+    width: u32;
+    height: u32;
+    birth: u32;
+    survival: u32;
+    topology: u32;
+};
+
+struct LiveCells {
+    indices: array<u32>;
+};
+
+[[group(1), binding(0)]] var<uniform> params: Params;
+[[group(1), binding(1)]] var<storage, read> live: LiveCells;
+
+[[stage(vertex)]]
+fn vs_cells(
+    model: VertexInput,
+    [[builtin(instance_index)]] instance: u32,
+) -> VertexOutput {
+    let index = live.indices[instance];
+    let center = vec2<f32>(f32(index % params.width), f32(index / params.width)) + vec2<f32>(0.5, 0.5);
+
+    var out: VertexOutput;
+    out.color = model.color;
+    out.clip_position = camera.view_proj * vec4<f32>(center + model.position.xy, 0.0, 1.0);
+    return out;
+}
@@ -0,0 +1,162 @@
+//!离屏渲染与 PNG 截图
+//!渲染目标除了窗口的 Surface,也可以是一张离屏纹理,绘制后复制到缓冲区读回并编码为 PNG
+//!不需要窗口,可以在没有显示器的 CI 中使用 wgpu 的软件(fallback)适配器生成图片并与基准图片比较
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::num::NonZeroU32;
+use std::path::Path;
+
+use wgpu::*;
+
+use crate::board::Board;
+use crate::camera::Camera;
+use crate::gpu::read_mapped;
+use crate::grid::GridRenderer;
+use crate::view::View;
+
+//render_board 使用的纹理格式
+pub const SNAPSHOT_FORMAT: TextureFormat = TextureFormat::Rgba8UnormSrgb;
+
+//可以作为渲染目标并读回的离屏纹理
+pub struct Offscreen {
+    texture: Texture,
+    view: TextureView,
+    width: u32,
+    height: u32,
+    format: TextureFormat,
+}
+
+impl Offscreen {
+    //format 只支持每像素 4 字节的 RGBA 或 BGRA 格式
+    pub fn new(device: &Device, width: u32, height: u32, format: TextureFormat) -> Self {
+        let texture = device.create_texture(&TextureDescriptor {
+            label: Some("Offscreen Texture"),
+            size: Extent3d { width, height, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format,
+            usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC,
+        });
+        let view = texture.create_view(&TextureViewDescriptor::default());
+        Offscreen { texture, view, width, height, format }
+    }
+
+    pub fn view(&self) -> &TextureView {
+        &self.view
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    //读回纹理内容,按行从上到下排列的 RGBA 像素
+    pub fn read_rgba(&self, device: &Device, queue: &Queue) -> Vec<u8> {
+        //复制到缓冲区时每行的字节数需要按 256 字节对齐
+        let row_bytes = self.width * 4;
+        let padded_row_bytes = row_bytes.div_ceil(COPY_BYTES_PER_ROW_ALIGNMENT) * COPY_BYTES_PER_ROW_ALIGNMENT;
+        let staging_buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("Offscreen Staging Buffer"),
+            size: (padded_row_bytes * self.height) as BufferAddress,
+            usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("Offscreen Readback Encoder"),
+        });
+        encoder.copy_texture_to_buffer(
+            ImageCopyTexture {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: Origin3d::ZERO,
+                aspect: TextureAspect::All,
+            },
+            ImageCopyBuffer {
+                buffer: &staging_buffer,
+                layout: ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: NonZeroU32::new(padded_row_bytes),
+                    rows_per_image: None,
+                },
+            },
+            Extent3d { width: self.width, height: self.height, depth_or_array_layers: 1 },
+        );
+        queue.submit(std::iter::once(encoder.finish()));
+
+        let data = read_mapped(device, &staging_buffer);
+        let bgra = matches!(self.format, TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb);
+        let mut rgba = Vec::with_capacity((row_bytes * self.height) as usize);
+        for row in data.chunks(padded_row_bytes as usize) {
+            for pixel in row[..row_bytes as usize].chunks(4) {
+                match bgra {
+                    true => rgba.extend_from_slice(&[pixel[2], pixel[1], pixel[0], pixel[3]]),
+                    false => rgba.extend_from_slice(pixel),
+                }
+            }
+        }
+        rgba
+    }
+}
+
+//创建不依赖窗口的设备,优先使用软件适配器,保证不同机器上生成的图片一致
+pub fn request_device() -> Option<(Device, Queue)> {
+    let instance = Instance::new(Backends::all());
+    let adapter = [true, false].iter().find_map(|&force_fallback_adapter| {
+        pollster::block_on(instance.request_adapter(&RequestAdapterOptions {
+            power_preference: PowerPreference::default(),
+            compatible_surface: None,
+            force_fallback_adapter,
+        }))
+    })?;
+    pollster::block_on(adapter.request_device(&DeviceDescriptor::default(), None)).ok()
+}
+
+//用纹理全屏绘制把整个棋盘渲染为 RGBA 图片,每个细胞占 cell_size x cell_size 像素
+//结果与 raster::rasterize 使用默认配色时逐像素一致
+pub fn render_board(device: &Device, queue: &Queue, board: &Board, cell_size: u32, grid_lines: bool) -> Vec<u8> {
+    let (board_width, board_height) = (board.width() as u32, board.height() as u32);
+    let (width, height) = (board_width * cell_size, board_height * cell_size);
+    let view = View::new(board.width(), board.height(), width, height);
+    let camera = Camera::new(device, &view);
+    let mut grid = GridRenderer::new(device, SNAPSHOT_FORMAT, &camera, board_width, board_height, None);
+    grid.upload(queue, board);
+    grid.set_grid_lines(queue, grid_lines);
+
+    let target = Offscreen::new(device, width, height, SNAPSHOT_FORMAT);
+    let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+        label: Some("Snapshot Encoder"),
+    });
+    {
+        let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
+            label: Some("Snapshot Pass"),
+            color_attachments: &[RenderPassColorAttachment {
+                view: target.view(),
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Clear(Color::BLACK),
+                    store: true,
+                },
+            }],
+            depth_stencil_attachment: None,
+        });
+        grid.draw(&mut render_pass, &camera, None);
+    }
+    queue.submit(std::iter::once(encoder.finish()));
+    target.read_rgba(device, queue)
+}
+
+//把按行从上到下排列的 RGBA 像素编码为 PNG
+pub fn encode_png<W: Write>(writer: W, width: u32, height: u32, rgba: &[u8]) -> io::Result<()> {
+    let mut encoder = png::Encoder::new(writer, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(io::Error::other)?;
+    writer.write_image_data(rgba).map_err(io::Error::other)?;
+    writer.finish().map_err(io::Error::other)
+}
+
+pub fn save_png<P: AsRef<Path>>(path: P, width: u32, height: u32, rgba: &[u8]) -> io::Result<()> {
+    encode_png(BufWriter::new(File::create(path)?), width, height, rgba)
+}
@@ -0,0 +1,147 @@
+//!种群曲线
+//!把最近若干代的种群数量画成窗口左下角的一排竖条,叠加在棋盘之上
+//!尺寸以像素为单位,不随相机缩放
+
+use wgpu::*;
+use wgpu::util::DeviceExt;
+
+//最多显示的代数,每代一根竖条
+pub const SPARKLINE_LENGTH: usize = 240;
+//曲线区域的像素尺寸与到窗口边缘的距离
+const SPARKLINE_WIDTH: f32 = 240.0;
+const SPARKLINE_HEIGHT: f32 = 60.0;
+const SPARKLINE_MARGIN: f32 = 10.0;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct SparklineParams {
+    origin: [f32; 2],
+    size: [f32; 2],
+    count: u32,
+    //uniform 缓冲区按 16 字节对齐
+    _padding: [u32; 3],
+}
+
+pub struct SparklineRenderer {
+    params_buffer: Buffer,
+    bind_group: BindGroup,
+    //第 0 个值是背景,之后每根竖条一个 0 到 1 的高度
+    heights_buffer: Buffer,
+    //每次更新时复用,避免每帧分配
+    heights: Vec<f32>,
+    count: u32,
+    pipeline: RenderPipeline,
+}
+
+impl SparklineRenderer {
+    pub fn new(device: &Device, format: TextureFormat) -> Self {
+        let params = SparklineParams { origin: [0.0; 2], size: [0.0; 2], count: 0, _padding: [0; 3] };
+        let params_buffer = device.create_buffer_init(&util::BufferInitDescriptor {
+            label: Some("Sparkline Params Buffer"),
+            contents: bytemuck::bytes_of(&params),
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+        });
+        let heights_buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("Sparkline Heights Buffer"),
+            size: ((SPARKLINE_LENGTH + 1) * std::mem::size_of::<f32>()) as BufferAddress,
+            usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("Sparkline Bind Group Layout"),
+            entries: &[BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStages::VERTEX,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: BufferSize::new(std::mem::size_of::<SparklineParams>() as _),
+                },
+                count: None,
+            }],
+        });
+        let bind_group = device.create_bind_group(&BindGroupDescriptor {
+            label: Some("Sparkline Bind Group"),
+            layout: &bind_group_layout,
+            entries: &[BindGroupEntry { binding: 0, resource: params_buffer.as_entire_binding() }],
+        });
+
+        let shader = device.create_shader_module(&ShaderModuleDescriptor {
+            label: Some("Sparkline Shader"),
+            source: ShaderSource::Wgsl(include_str!("sparkline.wgsl").into()),
+        });
+        let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: Some("Sparkline Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+            label: Some("Sparkline Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[VertexBufferLayout {
+                    array_stride: std::mem::size_of::<f32>() as BufferAddress,
+                    step_mode: VertexStepMode::Instance,
+                    attributes: &[VertexAttribute { offset: 0, shader_location: 0, format: VertexFormat::Float32 }],
+                }],
+            },
+            fragment: Some(FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[ColorTargetState {
+                    format,
+                    blend: Some(BlendState::ALPHA_BLENDING),
+                    write_mask: ColorWrites::ALL,
+                }],
+            }),
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+            multiview: None,
+        });
+
+        SparklineRenderer {
+            params_buffer,
+            bind_group,
+            heights_buffer,
+            heights: Vec::with_capacity(SPARKLINE_LENGTH + 1),
+            count: 0,
+            pipeline,
+        }
+    }
+
+    //显示 populations 中最后 SPARKLINE_LENGTH 个值,按其中的最大值归一化
+    pub fn update(&mut self, queue: &Queue, populations: &[usize], screen_width: u32, screen_height: u32) {
+        let populations = &populations[populations.len().saturating_sub(SPARKLINE_LENGTH)..];
+        let max = populations.iter().copied().max().unwrap_or(0).max(1) as f32;
+        self.heights.clear();
+        self.heights.push(1.0);
+        self.heights.extend(populations.iter().map(|&population| population as f32 / max));
+        queue.write_buffer(&self.heights_buffer, 0, bytemuck::cast_slice(&self.heights));
+        self.count = populations.len() as u32;
+
+        //像素换算到裁剪空间,y 轴向上
+        let (width, height) = (screen_width.max(1) as f32, screen_height.max(1) as f32);
+        let params = SparklineParams {
+            origin: [SPARKLINE_MARGIN / width * 2.0 - 1.0, SPARKLINE_MARGIN / height * 2.0 - 1.0],
+            size: [SPARKLINE_WIDTH / width * 2.0, SPARKLINE_HEIGHT / height * 2.0],
+            count: self.count,
+            _padding: [0; 3],
+        };
+        queue.write_buffer(&self.params_buffer, 0, bytemuck::bytes_of(&params));
+    }
+
+    //没有数据时什么也不画
+    pub fn draw<'a>(&'a self, render_pass: &mut RenderPass<'a>) {
+        if self.count == 0 {
+            return;
+        }
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.heights_buffer.slice(..));
+        render_pass.draw(0..6, 0..self.count + 1);
+    }
+}
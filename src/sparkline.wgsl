@@ -0,0 +1,54 @@
+// 种群曲线
+// 在窗口左下角画一排竖条,每根竖条的高度是一代的种群数量相对于最大值的比例
+// 第 0 个实例是半透明的背景,之后每个实例是一根竖条
+
+struct Sparkline {
+    // 裁剪空间中矩形的左下角与尺寸
+    origin: vec2<f32>;
+    size: vec2<f32>;
+    // 竖条的数量
+    count: u32;
+};
+
+struct VertexOutput {
+    [[builtin(position)]] clip_position: vec4<f32>;
+    [[location(0)]] color: vec4<f32>;
+};
+
+[[group(0), binding(0)]] var<uniform> sparkline: Sparkline;
+
+[[stage(vertex)]]
+fn vs_main(
+    [[builtin(vertex_index)]] index: u32,
+    [[builtin(instance_index)]] instance: u32,
+    [[location(0)]] height: f32,
+) -> VertexOutput {
+    // 两个三角形组成的单位正方形
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(0.0, 0.0),
+        vec2<f32>(1.0, 0.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(0.0, 0.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(0.0, 1.0)
+    );
+    let corner = corners[index];
+
+    var out: VertexOutput;
+    var position: vec2<f32>;
+    if (instance == 0u) {
+        position = corner;
+        out.color = vec4<f32>(0.0, 0.0, 0.0, 0.6);
+    } else {
+        let bar = f32(instance - 1u);
+        position = vec2<f32>((bar + corner.x) / f32(sparkline.count), corner.y * height);
+        out.color = vec4<f32>(0.2, 0.8, 0.3, 0.9);
+    }
+    out.clip_position = vec4<f32>(sparkline.origin + position * sparkline.size, 0.0, 1.0);
+    return out;
+}
+
+[[stage(fragment)]]
+fn fs_main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    return in.color;
+}
@@ -0,0 +1,91 @@
+//!稀疏的无限宇宙
+//!只用哈希集合保存活细胞,坐标为 i64,图样可以不受棋盘边界限制地一直扩张
+//!每一代只统计活细胞周围的细胞,耗时与种群数量成正比,与空白区域的大小无关
+//!B0 规则会让无限多个死细胞同时出生,稀疏宇宙无法表示,这类规则只计算活细胞附近的细胞
+
+use std::collections::{HashMap, HashSet};
+
+use crate::board::Board;
+use crate::engine::Engine;
+use crate::rule::Rule;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SparseUniverse {
+    cells: HashSet<(i64, i64)>,
+}
+
+impl SparseUniverse {
+    //创建一个空的宇宙
+    pub fn new() -> Self {
+        SparseUniverse::default()
+    }
+
+    //由活细胞坐标创建
+    pub fn from_cells<I: IntoIterator<Item = (i64, i64)>>(cells: I) -> Self {
+        SparseUniverse { cells: cells.into_iter().collect() }
+    }
+
+    //复制稠密棋盘上的活细胞,坐标保持不变
+    pub fn from_board(board: &Board) -> Self {
+        SparseUniverse::from_cells(board.live_cells())
+    }
+
+    //把以 (x, y) 为左下角、宽 x 高 的区域复制到稠密棋盘
+    pub fn to_board(&self, x: i64, y: i64, width: usize, height: usize) -> Board {
+        let mut board = Board::new(width, height);
+        for &(cell_x, cell_y) in &self.cells {
+            let (dx, dy) = (cell_x - x, cell_y - y);
+            if dx >= 0 && dy >= 0 && (dx as usize) < width && (dy as usize) < height {
+                board.set(dx as usize, dy as usize, true);
+            }
+        }
+        board
+    }
+}
+
+impl Engine for SparseUniverse {
+    fn step(&mut self, rule: &Rule) {
+        //每个活细胞给周围 8 个细胞各加一个邻居
+        let mut neighbors: HashMap<(i64, i64), u8> = HashMap::with_capacity(self.cells.len() * 8);
+        for &(x, y) in &self.cells {
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    if (dx, dy) != (0, 0) {
+                        *neighbors.entry((x + dx, y + dy)).or_insert(0) += 1;
+                    }
+                }
+            }
+        }
+
+        let mut next: HashSet<(i64, i64)> = neighbors
+            .iter()
+            .filter(|&(cell, &count)| rule.next_state(self.cells.contains(cell), count as usize))
+            .map(|(&cell, _)| cell)
+            .collect();
+        //没有邻居的活细胞不在 neighbors 中
+        if rule.survives(0) {
+            next.extend(self.cells.iter().filter(|cell| !neighbors.contains_key(cell)));
+        }
+        self.cells = next;
+    }
+
+    fn get(&self, x: i64, y: i64) -> bool {
+        self.cells.contains(&(x, y))
+    }
+
+    fn set(&mut self, x: i64, y: i64, alive: bool) {
+        if alive {
+            self.cells.insert((x, y));
+        } else {
+            self.cells.remove(&(x, y));
+        }
+    }
+
+    fn population(&self) -> usize {
+        self.cells.len()
+    }
+
+    fn live_cells(&self) -> Vec<(i64, i64)> {
+        self.cells.iter().copied().collect()
+    }
+}
@@ -0,0 +1,220 @@
+//!逐代统计
+//!记录每一代的种群数量、出生与死亡的细胞数以及活细胞的包围盒
+//!出生与死亡通过与上一代的活细胞比较得到,上一代没有被观察时为 None
+//!可以保存为 CSV 或 JSON
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::engine::Engine;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GenerationStats {
+    pub generation: u64,
+    pub population: usize,
+    //与上一代相比新出现的活细胞
+    pub births: Option<usize>,
+    //与上一代相比死亡的细胞
+    pub deaths: Option<usize>,
+    //(min_x, min_y, max_x, max_y),没有活细胞时为 None
+    pub bounding_box: Option<(i64, i64, i64, i64)>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatsFormat {
+    Csv,
+    Json,
+}
+
+impl StatsFormat {
+    pub fn from_path(path: &Path) -> Option<StatsFormat> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "csv" => Some(StatsFormat::Csv),
+            "json" => Some(StatsFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+pub struct Statistics {
+    history: VecDeque<GenerationStats>,
+    //最多保留的代数,None 表示不限
+    capacity: Option<usize>,
+    //上一次观察的代数与排好序的活细胞,用于计算出生与死亡
+    previous: Option<(u64, Vec<(i64, i64)>)>,
+}
+
+impl Default for Statistics {
+    fn default() -> Self {
+        Statistics::new()
+    }
+}
+
+impl Statistics {
+    //保留所有代的统计
+    pub fn new() -> Self {
+        Statistics { history: VecDeque::new(), capacity: None, previous: None }
+    }
+
+    //只保留最近 history 代,超过时丢弃最早的一代
+    pub fn with_history(history: usize) -> Self {
+        Statistics { history: VecDeque::new(), capacity: Some(history.max(1)), previous: None }
+    }
+
+    pub fn history(&self) -> &VecDeque<GenerationStats> {
+        &self.history
+    }
+
+    pub fn latest(&self) -> Option<&GenerationStats> {
+        self.history.back()
+    }
+
+    pub fn clear(&mut self) {
+        self.history.clear();
+        self.previous = None;
+    }
+
+    //棋盘在两代之间被直接修改(例如鼠标绘制)时调用,下一代不统计出生与死亡
+    pub fn edited(&mut self) {
+        self.previous = None;
+    }
+
+    pub fn observe_engine(&mut self, generation: u64, engine: &dyn Engine) -> GenerationStats {
+        self.observe(generation, &engine.live_cells())
+    }
+
+    //记录第 generation 代的活细胞,上一次观察的是第 generation - 1 代时同时统计出生与死亡
+    pub fn observe(&mut self, generation: u64, cells: &[(i64, i64)]) -> GenerationStats {
+        let mut sorted = cells.to_vec();
+        sorted.sort_unstable();
+        let changes = match &self.previous {
+            Some((last, previous)) if last.checked_add(1) == Some(generation) => Some(changes(previous, &sorted)),
+            _ => None,
+        };
+        let stats = GenerationStats {
+            generation,
+            population: sorted.len(),
+            births: changes.map(|(births, _)| births),
+            deaths: changes.map(|(_, deaths)| deaths),
+            bounding_box: bounding_box(&sorted),
+        };
+        self.previous = Some((generation, sorted));
+        self.push(stats);
+        stats
+    }
+
+    //只记录种群数量与包围盒,不需要枚举活细胞,适合 HashLife 一次演化多代的情况
+    pub fn observe_population(&mut self, generation: u64, engine: &dyn Engine) -> GenerationStats {
+        let stats = GenerationStats {
+            generation,
+            population: engine.population(),
+            births: None,
+            deaths: None,
+            bounding_box: engine.bounding_box(),
+        };
+        self.previous = None;
+        self.push(stats);
+        stats
+    }
+
+    //只记录种群数量,例如每帧从 GPU 读回的活细胞数,没有包围盒
+    pub fn observe_count(&mut self, generation: u64, population: usize) -> GenerationStats {
+        let stats = GenerationStats { generation, population, births: None, deaths: None, bounding_box: None };
+        self.previous = None;
+        self.push(stats);
+        stats
+    }
+
+    fn push(&mut self, stats: GenerationStats) {
+        self.history.push_back(stats);
+        if self.capacity.is_some_and(|capacity| self.history.len() > capacity) {
+            self.history.pop_front();
+        }
+    }
+
+    //保存统计,格式由扩展名决定
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let format = StatsFormat::from_path(path).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "无法识别统计文件的格式,可选 .csv、.json")
+        })?;
+        let mut writer = BufWriter::new(File::create(path)?);
+        match format {
+            StatsFormat::Csv => self.write_csv(&mut writer)?,
+            StatsFormat::Json => self.write_json(&mut writer)?,
+        }
+        writer.flush()
+    }
+
+    //每代一行,没有的值留空
+    pub fn write_csv<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(writer, "generation,population,births,deaths,min_x,min_y,max_x,max_y")?;
+        for stats in &self.history {
+            let optional = |value: Option<usize>| value.map(|value| value.to_string()).unwrap_or_default();
+            let bounds = match stats.bounding_box {
+                Some((min_x, min_y, max_x, max_y)) => format!("{},{},{},{}", min_x, min_y, max_x, max_y),
+                None => ",,,".to_string(),
+            };
+            writeln!(
+                writer,
+                "{},{},{},{},{}",
+                stats.generation,
+                stats.population,
+                optional(stats.births),
+                optional(stats.deaths),
+                bounds
+            )?;
+        }
+        Ok(())
+    }
+
+    //对象数组,每代一行,没有的值为 null
+    pub fn write_json<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(writer, "[")?;
+        for (i, stats) in self.history.iter().enumerate() {
+            let optional = |value: Option<usize>| value.map(|value| value.to_string()).unwrap_or_else(|| "null".to_string());
+            let bounds = match stats.bounding_box {
+                Some((min_x, min_y, max_x, max_y)) => format!("[{}, {}, {}, {}]", min_x, min_y, max_x, max_y),
+                None => "null".to_string(),
+            };
+            let separator = if i + 1 < self.history.len() { "," } else { "" };
+            writeln!(
+                writer,
+                "  {{\"generation\": {}, \"population\": {}, \"births\": {}, \"deaths\": {}, \"bounding_box\": {}}}{}",
+                stats.generation,
+                stats.population,
+                optional(stats.births),
+                optional(stats.deaths),
+                bounds,
+                separator
+            )?;
+        }
+        writeln!(writer, "]")
+    }
+}
+
+//两组排好序的活细胞之间出生与死亡的数量
+fn changes(previous: &[(i64, i64)], current: &[(i64, i64)]) -> (usize, usize) {
+    let (mut i, mut j, mut survivors) = (0, 0, 0);
+    while i < previous.len() && j < current.len() {
+        match previous[i].cmp(&current[j]) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                survivors += 1;
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    (current.len() - survivors, previous.len() - survivors)
+}
+
+fn bounding_box(cells: &[(i64, i64)]) -> Option<(i64, i64, i64, i64)> {
+    let &(x, y) = cells.first()?;
+    Some(cells.iter().fold((x, y, x, y), |(min_x, min_y, max_x, max_y), &(x, y)| {
+        (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y))
+    }))
+}
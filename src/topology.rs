@@ -0,0 +1,89 @@
+//!棋盘边界的拓扑结构
+//!决定计算邻居时越过棋盘边缘的坐标映射到哪个细胞
+
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Topology {
+    //有界平面,边界外的细胞视为死细胞
+    #[default]
+    Bounded,
+    //环面,左右、上下边缘分别相连
+    Torus,
+    //克莱因瓶,左右边缘直接相连,越过上下边缘时左右翻转
+    KleinBottle,
+    //交叉帽(射影平面),越过任一边缘时另一个方向翻转
+    //同时越过两条边缘的角落邻居没有明确定义,视为死细胞
+    CrossSurface,
+}
+
+impl Topology {
+    pub const ALL: &'static [Topology] = &[
+        Topology::Bounded,
+        Topology::Torus,
+        Topology::KleinBottle,
+        Topology::CrossSurface,
+    ];
+
+    //把可能越界的坐标映射回棋盘内,没有对应细胞时返回 None
+    pub fn wrap(&self, x: isize, y: isize, width: usize, height: usize) -> Option<(usize, usize)> {
+        let (w, h) = (width as isize, height as isize);
+        let x_inside = (0..w).contains(&x);
+        let y_inside = (0..h).contains(&y);
+        if x_inside && y_inside {
+            return Some((x as usize, y as usize));
+        }
+
+        let (x, y) = match self {
+            Topology::Bounded => return None,
+            Topology::Torus => (x.rem_euclid(w), y.rem_euclid(h)),
+            Topology::KleinBottle => {
+                let x = x.rem_euclid(w);
+                if y_inside {
+                    (x, y)
+                } else {
+                    (w - 1 - x, y.rem_euclid(h))
+                }
+            }
+            Topology::CrossSurface => match (x_inside, y_inside) {
+                (false, true) => (x.rem_euclid(w), h - 1 - y),
+                (true, false) => (w - 1 - x, y.rem_euclid(h)),
+                _ => return None,
+            },
+        };
+        Some((x as usize, y as usize))
+    }
+
+    //循环切换到下一种拓扑
+    pub fn next(&self) -> Topology {
+        let index = Topology::ALL.iter().position(|t| t == self).unwrap_or(0);
+        Topology::ALL[(index + 1) % Topology::ALL.len()]
+    }
+}
+
+impl fmt::Display for Topology {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Topology::Bounded => "bounded",
+            Topology::Torus => "torus",
+            Topology::KleinBottle => "klein",
+            Topology::CrossSurface => "cross",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Topology {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "bounded" | "plane" | "dead" => Ok(Topology::Bounded),
+            "torus" => Ok(Topology::Torus),
+            "klein" | "klein-bottle" => Ok(Topology::KleinBottle),
+            "cross" | "cross-surface" | "projective" => Ok(Topology::CrossSurface),
+            _ => Err(format!("未知的边界拓扑 `{}`,可选 bounded、torus、klein、cross", s)),
+        }
+    }
+}
@@ -0,0 +1,167 @@
+//!屏幕与棋盘之间的坐标变换
+//!渲染与鼠标拾取共用同一个 View,保证点击的位置就是画出来的细胞
+//!屏幕坐标以物理像素为单位,原点在左上角,y 轴向下;棋盘坐标以细胞为单位,原点在左下角,y 轴向上
+//!相机由缩放倍数与屏幕中心对准的棋盘坐标决定,缩放倍数为 1 时整个棋盘居中显示
+
+use cgmath::{Matrix4, SquareMatrix, Vector3};
+
+//最小缩放倍数,此时整个棋盘缩小到屏幕的一半
+pub const MIN_ZOOM: f64 = 0.5;
+//放大时细胞边长的上限,单位为物理像素
+pub const MAX_CELL_SIZE: f64 = 256.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct View {
+    board_width: usize,
+    board_height: usize,
+    screen_width: u32,
+    screen_height: u32,
+    zoom: f64,
+    min_zoom: f64,
+    //屏幕中心对准的棋盘坐标
+    center: (f64, f64),
+}
+
+impl View {
+    pub fn new(board_width: usize, board_height: usize, screen_width: u32, screen_height: u32) -> Self {
+        let mut view = View {
+            board_width: board_width.max(1),
+            board_height: board_height.max(1),
+            screen_width: screen_width.max(1),
+            screen_height: screen_height.max(1),
+            zoom: 1.0,
+            min_zoom: MIN_ZOOM,
+            center: (0.0, 0.0),
+        };
+        view.reset();
+        view
+    }
+
+    //窗口大小变化时更新,单位为物理像素
+    pub fn resize(&mut self, screen_width: u32, screen_height: u32) {
+        self.screen_width = screen_width.max(1);
+        self.screen_height = screen_height.max(1);
+    }
+
+    pub fn screen_size(&self) -> (u32, u32) {
+        (self.screen_width, self.screen_height)
+    }
+
+    pub fn zoom(&self) -> f64 {
+        self.zoom
+    }
+
+    pub fn center(&self) -> (f64, f64) {
+        self.center
+    }
+
+    //修改最小缩放倍数,没有边界的宇宙需要缩得更小才能看到扩张后的图样
+    pub fn set_min_zoom(&mut self, min_zoom: f64) {
+        self.min_zoom = min_zoom.min(1.0);
+        self.zoom = self.zoom.max(self.min_zoom);
+    }
+
+    //细胞在屏幕上的边长,细胞始终是正方形
+    pub fn cell_size(&self) -> f64 {
+        self.fit_cell_size(self.board_width as f64, self.board_height as f64) * self.zoom
+    }
+
+    //恢复为整个棋盘居中显示
+    pub fn reset(&mut self) {
+        self.zoom = 1.0;
+        self.center = (self.board_width as f64 / 2.0, self.board_height as f64 / 2.0);
+    }
+
+    //缩放并保持屏幕位置 (x, y) 下的棋盘坐标不变,factor 大于 1 时放大
+    pub fn zoom_at(&mut self, x: f64, y: f64, factor: f64) {
+        let (board_x, board_y) = self.screen_to_board(x, y);
+        self.zoom = (self.zoom * factor).clamp(self.min_zoom, self.max_zoom());
+        let cell_size = self.cell_size();
+        self.center = (
+            board_x - (x - self.screen_width as f64 / 2.0) / cell_size,
+            board_y + (y - self.screen_height as f64 / 2.0) / cell_size,
+        );
+    }
+
+    //按屏幕像素平移,画面跟随 (dx, dy) 移动
+    pub fn pan(&mut self, dx: f64, dy: f64) {
+        let cell_size = self.cell_size();
+        self.center.0 -= dx / cell_size;
+        self.center.1 += dy / cell_size;
+    }
+
+    //让棋盘上 [min, max) 的矩形区域居中并尽量填满屏幕
+    pub fn fit(&mut self, min: (f64, f64), max: (f64, f64)) {
+        let width = (max.0 - min.0).max(1.0);
+        let height = (max.1 - min.1).max(1.0);
+        let full = self.fit_cell_size(self.board_width as f64, self.board_height as f64);
+        self.zoom = (self.fit_cell_size(width, height) / full).clamp(self.min_zoom, self.max_zoom());
+        self.center = ((min.0 + max.0) / 2.0, (min.1 + max.1) / 2.0);
+    }
+
+    //棋盘坐标到裁剪空间的视图投影矩阵
+    pub fn view_proj(&self) -> Matrix4<f32> {
+        let cell_size = self.cell_size();
+        let scale = Matrix4::from_nonuniform_scale(
+            (2.0 * cell_size / self.screen_width as f64) as f32,
+            (2.0 * cell_size / self.screen_height as f64) as f32,
+            1.0,
+        );
+        let translation = Matrix4::from_translation(Vector3::new(-self.center.0 as f32, -self.center.1 as f32, 0.0));
+        scale * translation
+    }
+
+    //裁剪空间到棋盘坐标,用于在片元着色器中查找细胞
+    pub fn inverse_view_proj(&self) -> Matrix4<f32> {
+        self.view_proj().invert().unwrap_or_else(Matrix4::identity)
+    }
+
+    //裁剪空间坐标对应的屏幕位置,与光栅化时视口变换的方式一致
+    pub fn clip_to_screen(&self, x: f32, y: f32) -> (f64, f64) {
+        (
+            (x as f64 + 1.0) / 2.0 * self.screen_width as f64,
+            (1.0 - y as f64) / 2.0 * self.screen_height as f64,
+        )
+    }
+
+    //屏幕位置对应的棋盘坐标
+    pub fn screen_to_board(&self, x: f64, y: f64) -> (f64, f64) {
+        let cell_size = self.cell_size();
+        (
+            self.center.0 + (x - self.screen_width as f64 / 2.0) / cell_size,
+            self.center.1 - (y - self.screen_height as f64 / 2.0) / cell_size,
+        )
+    }
+
+    //屏幕位置所在的细胞,可能在棋盘之外
+    pub fn screen_to_cell(&self, x: f64, y: f64) -> (isize, isize) {
+        let (board_x, board_y) = self.screen_to_board(x, y);
+        (board_x.floor() as isize, board_y.floor() as isize)
+    }
+
+    //屏幕上能看到的细胞范围 (左下角, 右上角),包含两端
+    pub fn visible_cells(&self) -> ((isize, isize), (isize, isize)) {
+        let min = self.screen_to_cell(0.0, self.screen_height as f64);
+        let max = self.screen_to_cell(self.screen_width as f64, 0.0);
+        (min, max)
+    }
+
+    //细胞中心的屏幕位置
+    pub fn cell_to_screen(&self, x: usize, y: usize) -> (f64, f64) {
+        let cell_size = self.cell_size();
+        (
+            self.screen_width as f64 / 2.0 + (x as f64 + 0.5 - self.center.0) * cell_size,
+            self.screen_height as f64 / 2.0 - (y as f64 + 0.5 - self.center.1) * cell_size,
+        )
+    }
+
+    //宽 x 高 个细胞正好放进屏幕时的细胞边长
+    fn fit_cell_size(&self, width: f64, height: f64) -> f64 {
+        (self.screen_width as f64 / width).min(self.screen_height as f64 / height)
+    }
+
+    fn max_zoom(&self) -> f64 {
+        let full = self.fit_cell_size(self.board_width as f64, self.board_height as f64);
+        (MAX_CELL_SIZE / full).max(1.0)
+    }
+}
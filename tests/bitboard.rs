@@ -0,0 +1,85 @@
+use conway_wgpu::{BitBoard, Board, Engine, Rule, Topology};
+
+//覆盖不足一个字、正好一个字、跨字以及只有一行/一列的棋盘
+const SIZES: &[(usize, usize)] = &[(1, 1), (3, 1), (1, 5), (5, 7), (63, 9), (64, 64), (65, 33), (130, 6)];
+
+fn assert_same(bits: &BitBoard, board: &Board, message: &str) {
+    assert_eq!(&bits.to_board(), board, "{}", message);
+    assert_eq!(bits.population(), board.population(), "{}", message);
+}
+
+//所有尺寸、拓扑与预设规则下逐代与 Board 的结果完全一致
+#[test]
+fn matches_board_for_all_topologies_and_rules() {
+    for &(width, height) in SIZES {
+        for &topology in Topology::ALL {
+            for rule in Rule::PRESETS {
+                let mut board = Board::random(width, height).with_topology(topology);
+                let mut bits = BitBoard::from_board(&board);
+                assert_same(&bits, &board, "初始状态不一致");
+
+                for generation in 1..=20 {
+                    board.step(rule);
+                    bits.step(rule);
+                    let message = format!("{}x{} {} {} 第 {} 代不一致", width, height, topology, rule, generation);
+                    assert_same(&bits, &board, &message);
+                }
+            }
+        }
+    }
+}
+
+//按行分段多线程演化的结果与单线程一致,线程数多于行数时也能正常工作
+#[test]
+fn threads_match_single_thread() {
+    for threads in [2, 3, 8, 100] {
+        for &topology in Topology::ALL {
+            let mut board = Board::random(97, 41).with_topology(topology);
+            let mut bits = BitBoard::from_board(&board);
+            bits.set_threads(threads);
+            assert_eq!(bits.threads(), threads);
+
+            for generation in 1..=10 {
+                board.step(&Rule::CONWAY);
+                bits.step(&Rule::CONWAY);
+                assert_same(&bits, &board, &format!("{} 线程 {} 第 {} 代不一致", threads, topology, generation));
+            }
+        }
+    }
+}
+
+#[test]
+fn set_get_and_live_cells() {
+    let mut bits = BitBoard::new(70, 3);
+    bits.set(0, 0, true);
+    bits.set(63, 1, true);
+    bits.set(64, 1, true);
+    bits.set(69, 2, true);
+    bits.set(70, 2, true);
+    assert!(bits.get(63, 1) && bits.get(64, 1));
+    assert!(!bits.get(70, 2));
+    assert_eq!(bits.population(), 4);
+
+    let mut cells = Engine::live_cells(&bits);
+    cells.sort_unstable();
+    assert_eq!(cells, vec![(0, 0), (63, 1), (64, 1), (69, 2)]);
+
+    bits.set(64, 1, false);
+    assert_eq!(bits.population(), 3);
+    assert_eq!(Engine::bounding_box(&bits), Some((0, 0, 69, 2)));
+}
+
+//通过 Engine 接口切换拓扑
+#[test]
+fn engine_set_topology() {
+    let mut board = Board::random(20, 20);
+    let mut bits = BitBoard::from_board(&board);
+    Engine::set_topology(&mut board, Topology::Torus);
+    Engine::set_topology(&mut bits, Topology::Torus);
+    assert_eq!(bits.topology(), Topology::Torus);
+    for _ in 0..10 {
+        Engine::step(&mut board, &Rule::CONWAY);
+        Engine::step(&mut bits, &Rule::CONWAY);
+    }
+    assert_same(&bits, &board, "切换拓扑后不一致");
+}
@@ -0,0 +1,59 @@
+use conway_wgpu::{Board, Rule};
+
+fn board_from(width: usize, height: usize, cells: &[(usize, usize)]) -> Board {
+    let mut board = Board::new(width, height);
+    for &(x, y) in cells {
+        board.set(x, y, true);
+    }
+    board
+}
+
+#[test]
+fn blinker_oscillates() {
+    let mut board = board_from(5, 5, &[(1, 2), (2, 2), (3, 2)]);
+    board.step(&Rule::CONWAY);
+    assert_eq!(board, board_from(5, 5, &[(2, 1), (2, 2), (2, 3)]));
+    board.step(&Rule::CONWAY);
+    assert_eq!(board, board_from(5, 5, &[(1, 2), (2, 2), (3, 2)]));
+}
+
+#[test]
+fn block_is_still() {
+    let block = board_from(4, 4, &[(1, 1), (2, 1), (1, 2), (2, 2)]);
+    let mut board = block.clone();
+    board.step(&Rule::CONWAY);
+    assert_eq!(board, block);
+    assert_eq!(board.population(), 4);
+}
+
+#[test]
+fn out_of_bounds_is_dead() {
+    let mut board = Board::new(3, 2);
+    board.set(3, 0, true);
+    board.set(0, 2, true);
+    assert_eq!(board.population(), 0);
+    assert!(!board.get(10, 10));
+}
+
+#[test]
+fn highlife_replicator_differs_from_conway() {
+    // 6 个邻居时 HighLife 会出生而 B3/S23 不会
+    let cells = [(0, 0), (1, 0), (2, 0), (0, 2), (1, 2), (2, 2)];
+    let mut conway = board_from(3, 3, &cells);
+    let mut highlife = conway.clone();
+    conway.step(&Rule::CONWAY);
+    highlife.step(&Rule::HIGHLIFE);
+    assert!(!conway.get(1, 1));
+    assert!(highlife.get(1, 1));
+}
+
+#[test]
+fn bounding_box() {
+    let mut board = Board::new(10, 8);
+    assert_eq!(board.bounding_box(), None);
+    board.set(3, 5, true);
+    assert_eq!(board.bounding_box(), Some((3, 5, 3, 5)));
+    board.set(7, 1, true);
+    board.set(2, 6, true);
+    assert_eq!(board.bounding_box(), Some((2, 1, 7, 6)));
+}
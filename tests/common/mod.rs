@@ -0,0 +1,16 @@
+use std::io::Write;
+
+use conway_wgpu::snapshot;
+
+//优先使用 wgpu 的软件(fallback)适配器;没有任何适配器时跳过测试,并直接写到标准错误,不会被测试框架捕获
+//设置环境变量 CONWAY_REQUIRE_GPU 后没有适配器时测试失败,用于确认 CI 中确实运行了 GPU 测试
+pub fn device() -> Option<(wgpu::Device, wgpu::Queue)> {
+    let device = snapshot::request_device();
+    if device.is_none() {
+        if std::env::var_os("CONWAY_REQUIRE_GPU").is_some() {
+            panic!("没有可用的 wgpu 适配器,而 CONWAY_REQUIRE_GPU 要求运行 GPU 测试");
+        }
+        let _ = writeln!(std::io::stderr(), "没有可用的 wgpu 适配器,跳过 GPU 测试");
+    }
+    device
+}
@@ -0,0 +1,105 @@
+use conway_wgpu::{Board, GpuLife, Rule, Topology};
+
+mod common;
+
+fn assert_matches_cpu(device: &wgpu::Device, queue: &wgpu::Queue, mut board: Board, rule: Rule, generations: usize) {
+    let mut gpu = GpuLife::new(device, &board, &rule);
+    for generation in 1..=generations {
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        gpu.step(&mut encoder);
+        queue.submit(std::iter::once(encoder.finish()));
+        board.step(&rule);
+        assert_eq!(
+            gpu.read_board(device, queue),
+            board,
+            "{} {} 第 {} 代 GPU 与 CPU 结果不一致",
+            rule,
+            board.topology(),
+            generation
+        );
+    }
+}
+
+#[test]
+fn gpu_matches_cpu_reference() {
+    let (device, queue) = match common::device() {
+        Some(device) => device,
+        None => return,
+    };
+    for &topology in Topology::ALL {
+        for &rule in Rule::PRESETS {
+            // 非正方形且不是工作组大小整数倍的棋盘
+            let board = Board::random(37, 23).with_topology(topology);
+            assert_matches_cpu(&device, &queue, board, rule, 16);
+        }
+    }
+}
+
+#[test]
+fn gpu_edits_are_visible_to_next_step() {
+    let (device, queue) = match common::device() {
+        Some(device) => device,
+        None => return,
+    };
+    let board = Board::new(8, 8).with_topology(Topology::Torus);
+    let mut gpu = GpuLife::new(&device, &board, &Rule::CONWAY);
+    for x in 0..3 {
+        gpu.set_cell(&queue, x, 0, true);
+    }
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+    gpu.step(&mut encoder);
+    queue.submit(std::iter::once(encoder.finish()));
+
+    // 横向的闪光灯变成纵向,并跨越上下边界
+    let stepped = gpu.read_board(&device, &queue);
+    assert_eq!(stepped.population(), 3);
+    assert!(stepped.get(1, 7) && stepped.get(1, 0) && stepped.get(1, 1));
+}
+
+// 批量修改细胞的结果与逐个修改一致,重复与越界的细胞不影响结果
+#[test]
+fn set_cells_matches_set_cell() {
+    let (device, queue) = match common::device() {
+        Some(device) => device,
+        None => return,
+    };
+    let board = Board::random(21, 13);
+    let gpu = GpuLife::new(&device, &board, &Rule::CONWAY);
+    let mut expected = board.clone();
+    let cells = [(3, 2), (0, 2), (1, 2), (2, 2), (20, 0), (0, 1), (1, 2), (5, 12), (6, 12), (21, 3), (4, 13)];
+    gpu.set_cells(&queue, &cells, true);
+    for &(x, y) in &cells {
+        if x < 21 && y < 13 {
+            expected.set(x, y, true);
+        }
+    }
+    assert_eq!(gpu.read_board(&device, &queue), expected);
+
+    gpu.set_cells(&queue, &[(1, 2), (2, 2), (6, 12)], false);
+    for &(x, y) in &[(1, 2), (2, 2), (6, 12)] {
+        expected.set(x, y, false);
+    }
+    assert_eq!(gpu.read_board(&device, &queue), expected);
+}
+
+#[test]
+fn compact_counts_live_cells() {
+    let (device, queue) = match common::device() {
+        Some(device) => device,
+        None => return,
+    };
+    let rule = Rule::CONWAY;
+    let mut board = Board::random(37, 23).with_topology(Topology::Torus);
+    let mut gpu = GpuLife::new(&device, &board, &rule);
+    for _ in 0..5 {
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        gpu.compact(&queue, &mut encoder);
+        queue.submit(std::iter::once(encoder.finish()));
+        assert_eq!(gpu.read_instance_count(&device, &queue) as usize, board.population());
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        gpu.step(&mut encoder);
+        queue.submit(std::iter::once(encoder.finish()));
+        board.step(&rule);
+    }
+}
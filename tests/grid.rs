@@ -0,0 +1,24 @@
+use conway_wgpu::grid::board_to_texels;
+use conway_wgpu::Board;
+
+#[test]
+fn texels_follow_board_layout() {
+    let mut board = Board::new(4, 3);
+    board.set(0, 0, true);
+    board.set(3, 2, true);
+    board.set(1, 1, true);
+
+    let texels = board_to_texels(&board);
+    assert_eq!(texels.len(), 12);
+    //第一行纹素对应棋盘最下面一行
+    assert_eq!(&texels[0..4], &[255, 0, 0, 0]);
+    assert_eq!(&texels[4..8], &[0, 255, 0, 0]);
+    assert_eq!(&texels[8..12], &[0, 0, 0, 255]);
+}
+
+#[test]
+fn texel_count_matches_population() {
+    let board = Board::random(100, 80);
+    let live = board_to_texels(&board).iter().filter(|&&texel| texel == u8::MAX).count();
+    assert_eq!(live, board.population());
+}
@@ -0,0 +1,151 @@
+use std::collections::HashSet;
+
+use conway_wgpu::pattern::Format;
+use conway_wgpu::{Board, Engine, HashLife, Rule, SparseUniverse};
+
+const GOSPER_GUN: &str = "\
+#N Gosper glider gun
+x = 36, y = 9, rule = B3/S23
+24bo$22bobo$12b2o6b2o12b2o$11bo3bo4b2o12b2o$2o8bo5bo3b2o$2o8bo3bob2o4b
+obo$10bo5bo7bo$11bo3bo$12b2o!";
+
+fn live_set(engine: &dyn Engine) -> HashSet<(i64, i64)> {
+    engine.live_cells().into_iter().collect()
+}
+
+fn gun() -> Board {
+    Format::Rle.parse(GOSPER_GUN).unwrap().to_board()
+}
+
+//逐代演化的结果应当与稀疏宇宙完全一致
+#[test]
+fn matches_sparse_universe_step_by_step() {
+    for rule in Rule::PRESETS {
+        let soup = Board::random(30, 30);
+        let mut universe = SparseUniverse::from_board(&soup);
+        let mut hashlife = HashLife::from_board(&soup);
+        assert_eq!(live_set(&universe), live_set(&hashlife));
+
+        for generation in 1..=40 {
+            universe.step(rule);
+            hashlife.step(rule);
+            assert_eq!(live_set(&universe), live_set(&hashlife), "{} 第 {} 代不一致", rule, generation);
+        }
+    }
+}
+
+//一次跳过 2^N 代与逐代演化的结果一致
+#[test]
+fn jumps_powers_of_two() {
+    let board = Board::random(40, 40);
+    for log2 in 0..7 {
+        let mut universe = SparseUniverse::from_board(&board);
+        let mut hashlife = HashLife::from_board(&board);
+        universe.advance(&Rule::CONWAY, 1 << log2);
+        hashlife.step_pow2(&Rule::CONWAY, log2);
+        assert_eq!(live_set(&universe), live_set(&hashlife), "2^{} 代不一致", log2);
+    }
+}
+
+//任意代数按二进制拆分成若干次跳跃
+#[test]
+fn advance_splits_generations() {
+    let mut universe = SparseUniverse::from_board(&gun());
+    let mut hashlife = HashLife::from_board(&gun());
+    universe.advance(&Rule::CONWAY, 600);
+    hashlife.advance(&Rule::CONWAY, 600);
+    assert_eq!(hashlife.population(), 36 + 20 * 5);
+    assert_eq!(live_set(&universe), live_set(&hashlife));
+    assert_eq!(universe.bounding_box(), hashlife.bounding_box());
+}
+
+//周期性的图样可以跳过极大的代数
+#[test]
+fn glider_gun_after_astronomical_generations() {
+    let mut hashlife = HashLife::from_board(&gun());
+    let generations = 1u64 << 40;
+    hashlife.step_pow2(&Rule::CONWAY, 40);
+
+    //枪每 30 代回到原来的形态并多出一架 5 个细胞的滑翔机
+    let phase = generations % 30 + 60;
+    let mut original = SparseUniverse::from_board(&gun());
+    original.advance(&Rule::CONWAY, phase);
+    let gliders = (generations - phase) / 30;
+    assert_eq!(hashlife.population() as u64, original.population() as u64 + gliders * 5);
+
+    //枪仍然在原来的位置
+    let mut gun_cells = hashlife.live_cells_in((0, -10), (40, 10));
+    let mut expected = original.live_cells_in((0, -10), (40, 10));
+    gun_cells.sort_unstable();
+    expected.sort_unstable();
+    assert_eq!(gun_cells, expected);
+}
+
+#[test]
+fn set_get_and_negative_coordinates() {
+    let mut hashlife = HashLife::new();
+    hashlife.set(-5, -7, true);
+    hashlife.set(3, 4, true);
+    hashlife.set(1000, -2000, true);
+    assert!(hashlife.get(-5, -7));
+    assert!(hashlife.get(1000, -2000));
+    assert!(!hashlife.get(0, 0));
+    assert_eq!(hashlife.population(), 3);
+    assert_eq!(hashlife.bounding_box(), Some((-5, -2000, 1000, 4)));
+
+    hashlife.set(1000, -2000, false);
+    hashlife.set(i64::MAX, i64::MIN, false);
+    assert_eq!(hashlife.population(), 2);
+    assert_eq!(hashlife.live_cells_in((-10, -10), (0, 0)), vec![(-5, -7)]);
+}
+
+//直接由相距很远的活细胞建立四叉树,不经过稠密棋盘
+#[test]
+fn from_cells_far_apart() {
+    let block = [(0, 0), (1, 0), (0, 1), (1, 1)];
+    let far = 1 << 40;
+    let cells: Vec<_> = block.iter().flat_map(|&(x, y)| [(x, y), (x + far, y + far)]).collect();
+    let mut hashlife = HashLife::from_cells(cells.iter().copied());
+    hashlife.advance(&Rule::CONWAY, 1000);
+    assert_eq!(live_set(&hashlife), cells.into_iter().collect());
+
+    let extreme = HashLife::from_cells([(i64::MIN, i64::MIN), (i64::MAX, i64::MAX)]);
+    assert_eq!(extreme.population(), 2);
+}
+
+//回收后只保留当前图样用到的节点,继续演化的结果不变
+#[test]
+fn garbage_collection_keeps_pattern() {
+    let mut hashlife = HashLife::from_board(&gun());
+    let mut universe = SparseUniverse::from_board(&gun());
+    hashlife.advance(&Rule::CONWAY, 300);
+    universe.advance(&Rule::CONWAY, 300);
+
+    let before = hashlife.node_count();
+    hashlife.collect_garbage();
+    assert!(hashlife.node_count() < before);
+    assert_eq!(live_set(&universe), live_set(&hashlife));
+
+    hashlife.advance(&Rule::CONWAY, 100);
+    universe.advance(&Rule::CONWAY, 100);
+    assert_eq!(live_set(&universe), live_set(&hashlife));
+}
+
+//超过内存上限时在演化前自动回收,节点数量不再只增不减
+#[test]
+fn memory_limit_triggers_collection() {
+    let board = Board::random(64, 64);
+    let mut hashlife = HashLife::from_board(&board);
+    let mut universe = SparseUniverse::from_board(&board);
+    hashlife.set_memory_limit(0);
+
+    let mut collected = false;
+    for _ in 0..50 {
+        let before = hashlife.node_count();
+        hashlife.step(&Rule::CONWAY);
+        universe.step(&Rule::CONWAY);
+        collected |= hashlife.node_count() < before;
+    }
+    assert!(collected);
+    assert_eq!(live_set(&universe), live_set(&hashlife));
+}
@@ -0,0 +1,54 @@
+use conway_wgpu::paint::{brush, line, stroke, MAX_BRUSH_SIZE};
+
+//相邻两个细胞在横向与纵向上最多相差 1
+fn assert_connected(cells: &[(isize, isize)]) {
+    for pair in cells.windows(2) {
+        let (a, b) = (pair[0], pair[1]);
+        assert!((a.0 - b.0).abs() <= 1 && (a.1 - b.1).abs() <= 1, "{:?} 与 {:?} 之间有空隙", a, b);
+    }
+}
+
+#[test]
+fn line_covers_both_ends_without_gaps() {
+    for &(from, to) in &[
+        ((0, 0), (0, 0)),
+        ((0, 0), (10, 0)),
+        ((3, 9), (3, -4)),
+        ((0, 0), (7, 3)),
+        ((-5, 2), (6, -9)),
+        ((12, 1), (0, 30)),
+    ] {
+        let cells = line(from, to);
+        assert_eq!(cells.first(), Some(&from));
+        assert_eq!(cells.last(), Some(&to));
+        assert_eq!(cells.len() as isize, (to.0 - from.0).abs().max((to.1 - from.1).abs()) + 1);
+        assert_connected(&cells);
+    }
+}
+
+#[test]
+fn brush_is_square_around_center() {
+    assert_eq!(brush((4, 4), 1).collect::<Vec<_>>(), vec![(4, 4)]);
+
+    let cells: Vec<_> = brush((4, 4), 3).collect();
+    assert_eq!(cells.len(), 9);
+    assert!(cells.iter().all(|&(x, y)| (3..=5).contains(&x) && (3..=5).contains(&y)));
+
+    assert_eq!(brush((0, 0), 0).count(), 1);
+    assert_eq!(brush((0, 0), usize::MAX).count(), MAX_BRUSH_SIZE * MAX_BRUSH_SIZE);
+}
+
+#[test]
+fn stroke_is_clipped_and_deduplicated() {
+    let cells = stroke((-3, 1), (12, 1), 3, 10, 4);
+    //三行,每行 0..10
+    assert_eq!(cells.len(), 30);
+    assert!(cells.iter().all(|&(x, y)| x < 10 && y < 3));
+
+    let mut sorted = cells.clone();
+    sorted.sort();
+    sorted.dedup();
+    assert_eq!(sorted.len(), cells.len());
+
+    assert!(stroke((-5, -5), (-1, -1), 1, 10, 10).is_empty());
+}
@@ -0,0 +1,73 @@
+use conway_wgpu::grid::board_to_texels;
+use conway_wgpu::{BitBoard, Board, Engine, ParallelBoard, Rule, SparseUniverse, Topology};
+
+//不同线程数下 par_step 与 step 的结果逐位一致,包括行数少于分段数的棋盘
+#[test]
+fn par_step_matches_serial_step() {
+    for &(width, height) in &[(1, 1), (7, 3), (50, 41), (33, 200)] {
+        for &topology in Topology::ALL {
+            for rule in Rule::PRESETS {
+                let mut serial = Board::random(width, height).with_topology(topology);
+                let mut parallel = serial.clone();
+                for generation in 1..=10 {
+                    serial.step(rule);
+                    parallel.par_step(rule);
+                    assert_eq!(serial, parallel, "{}x{} {} {} 第 {} 代不一致", width, height, topology, rule, generation);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn thread_count_is_configurable() {
+    let board = Board::random(64, 64).with_topology(Topology::Torus);
+    for threads in [1, 2, 3, 8] {
+        let mut serial = board.clone();
+        let mut parallel = ParallelBoard::new(board.clone(), threads).unwrap();
+        assert_eq!(parallel.threads(), threads);
+        for _ in 0..20 {
+            serial.step(&Rule::HIGHLIFE);
+            parallel.step(&Rule::HIGHLIFE);
+        }
+        assert_eq!(parallel.board(), &serial, "{} 线程不一致", threads);
+        assert_eq!(parallel.population(), serial.population());
+    }
+}
+
+#[test]
+fn engine_interface() {
+    let mut parallel = ParallelBoard::new(Board::new(10, 10), 2).unwrap();
+    //横向的闪烁者
+    for x in 3..6 {
+        parallel.set(x, 5, true);
+    }
+    parallel.set(-1, 5, true);
+    assert_eq!(parallel.population(), 3);
+    parallel.step(&Rule::CONWAY);
+    assert_eq!(parallel.bounding_box(), Some((4, 4, 4, 6)));
+
+    parallel.set_topology(Topology::KleinBottle);
+    let board = parallel.into_board();
+    assert_eq!(board.topology(), Topology::KleinBottle);
+    assert!(board.get(4, 4) && board.get(4, 6));
+}
+
+//有边界的引擎都能写出与 board_to_texels 相同的纹理数据,没有边界的引擎不能
+#[test]
+fn texels_of_bounded_engines() {
+    let board = Board::random(70, 9).with_topology(Topology::KleinBottle);
+    let parallel = ParallelBoard::new(board.clone(), 2).unwrap();
+    let bitboard = BitBoard::from_board(&board);
+    let expected = board_to_texels(&board);
+    //缓冲区中原有的数据会被覆盖
+    let mut texels = vec![1; 3];
+    assert!(Engine::write_texels(&board, &mut texels));
+    assert_eq!(texels, expected);
+    assert!(parallel.write_texels(&mut texels));
+    assert_eq!(texels, expected);
+    texels = vec![1; 1000];
+    assert!(bitboard.write_texels(&mut texels));
+    assert_eq!(texels, expected);
+    assert!(!SparseUniverse::from_cells([(0, 0)]).write_texels(&mut texels));
+}
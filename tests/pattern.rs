@@ -0,0 +1,195 @@
+use std::path::Path;
+
+use conway_wgpu::pattern::{self, life105, life106, plaintext, rle, Format, PatternError};
+use conway_wgpu::{Board, Pattern, Rule};
+
+// 滑翔机的活细胞坐标 (列, 行),行号从上往下数
+const GLIDER: [(usize, usize); 5] = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+
+fn random_pattern() -> Pattern {
+    let coordinates = (0..200)
+        .map(|_| (rand::random::<i64>() % 60, rand::random::<i64>() % 40))
+        .collect::<Vec<_>>();
+    Pattern::from_coordinates(&coordinates)
+}
+
+const GLIDER_RLE: &str = "\
+#N Glider
+#O Richard K. Guy
+#C The smallest, most common, and first discovered spaceship.
+x = 3, y = 3, rule = B3/S23
+bob$2bo$3o!
+";
+
+#[test]
+fn parses_rle_glider() {
+    let pattern = rle::parse(GLIDER_RLE).unwrap();
+    assert_eq!(pattern.name.as_deref(), Some("Glider"));
+    assert_eq!(pattern.author.as_deref(), Some("Richard K. Guy"));
+    assert_eq!(pattern.comments.len(), 1);
+    assert_eq!(pattern.rule, Some(Rule::CONWAY));
+    assert_eq!((pattern.width, pattern.height), (3, 3));
+    assert_eq!(pattern.cells, vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]);
+}
+
+#[test]
+fn rle_header_rule_and_multiline_body() {
+    let pattern = rle::parse("x = 12, y = 3, rule = 23/36\n2o8b\n2o$\n3$o!").unwrap();
+    assert_eq!(pattern.rule, Some(Rule::HIGHLIFE));
+    assert_eq!(pattern.cells, vec![(0, 0), (1, 0), (10, 0), (11, 0), (0, 4)]);
+    assert_eq!((pattern.width, pattern.height), (12, 5));
+}
+
+#[test]
+fn rle_reports_errors() {
+    assert!(rle::parse("#C only comments\n").is_err());
+    assert!(rle::parse("x = 3, y = 3, rule = B9/S23\nooo!").is_err());
+    assert!(rle::parse("x = 3, y = 3\noqo!").is_err());
+}
+
+//很大的数字返回错误,不会溢出或分配大量内存
+#[test]
+fn rle_rejects_huge_sizes() {
+    assert!(rle::parse("x = 99999999999999999999999, y = 3\no!").is_err());
+    assert!(rle::parse("x = 3, y = 4000000000\no!").is_err());
+    assert!(rle::parse("x = 3, y = 3\n99999999999999999999999o!").is_err());
+    assert!(rle::parse("x = 3, y = 3\n1048577o!").is_err());
+    assert!(rle::parse("x = 3, y = 3\n1048576b1048576bo!").is_err());
+    assert!(rle::parse("x = 3, y = 3\n1048576$o!").is_err());
+    let many = format!("x = 3, y = 3\n{}!", "1048576o$".repeat(17));
+    assert!(rle::parse(&many).is_err());
+
+    //宽高很大但活细胞很少的图样可以正常写出,但不能转换为稠密棋盘
+    let sparse = rle::parse("x = 1048576, y = 1048576\no!").unwrap();
+    assert_eq!(rle::write(&sparse), "x = 1048576, y = 1048576\no!\n");
+    assert!(matches!(
+        pattern::check_board_size(sparse.width, sparse.height),
+        Err(PatternError::TooLarge { width: 1048576, height: 1048576 })
+    ));
+}
+
+//包围盒很大的图样只能写成 RLE 与 Life 1.06,逐格写出的格式返回错误
+#[test]
+fn text_formats_reject_huge_patterns() {
+    let sparse = rle::parse("x = 1048576, y = 1048576\no!").unwrap();
+    assert!(Format::Rle.write(&sparse).is_ok());
+    assert!(Format::Life106.write(&sparse).is_ok());
+    assert!(matches!(Format::Plaintext.write(&sparse), Err(PatternError::TooLargeForText { .. })));
+    assert!(matches!(Format::Life105.write(&sparse), Err(PatternError::TooLargeForText { .. })));
+    let path = std::env::temp_dir().join("conway-too-large.cells");
+    assert!(pattern::save(&path, &sparse).is_err());
+    assert!(!path.exists());
+}
+
+#[test]
+fn board_size_limits() {
+    assert!(pattern::check_board_size(4096, 4096).is_ok());
+    assert!(pattern::check_board_size(1, pattern::MAX_DIMENSION).is_ok());
+    assert!(pattern::check_board_size(pattern::MAX_DIMENSION + 1, 1).is_err());
+    assert!(pattern::check_board_size(1 << 15, 1 << 15).is_err());
+    assert!(pattern::check_board_size(usize::MAX, usize::MAX).is_err());
+}
+
+#[test]
+fn rle_round_trips_board() {
+    let mut board = Board::random(97, 41);
+    board.set(96, 0, true);
+    let mut pattern = Pattern::from_board(&board);
+    pattern.rule = Some(Rule::DAY_AND_NIGHT);
+    pattern.name = Some("soup".to_string());
+
+    let text = Format::Rle.write(&pattern).unwrap();
+    assert!(text.lines().all(|line| line.len() <= 70));
+    let parsed = Format::Rle.parse(&text).unwrap();
+    assert_eq!(parsed, pattern);
+    assert_eq!(parsed.to_board(), board);
+}
+
+#[test]
+fn parses_plaintext_glider() {
+    let pattern = plaintext::parse("!Name: Glider\n!A spaceship\n.O\n..O\nOOO\n").unwrap();
+    assert_eq!(pattern.name.as_deref(), Some("Glider"));
+    assert_eq!(pattern.comments, vec!["A spaceship".to_string()]);
+    assert_eq!((pattern.width, pattern.height), (3, 3));
+    assert_eq!(pattern.cells, GLIDER);
+    assert!(plaintext::parse(".O\n.X\n").is_err());
+}
+
+#[test]
+fn plaintext_round_trips() {
+    let mut pattern = random_pattern();
+    pattern.name = Some("soup".to_string());
+    pattern.comments = vec!["random".to_string()];
+    assert_eq!(plaintext::parse(&plaintext::write(&pattern)).unwrap(), pattern);
+}
+
+#[test]
+fn parses_life106_glider() {
+    let pattern = life106::parse("#Life 1.06\n0 -1\n1 0\n-1 1\n0 1\n1 1\n").unwrap();
+    assert_eq!((pattern.width, pattern.height), (3, 3));
+    assert_eq!(pattern.cells, GLIDER);
+    assert!(life106::parse("#Life 1.06\n0 a\n").is_err());
+}
+
+#[test]
+fn life106_round_trips() {
+    let pattern = random_pattern();
+    assert_eq!(life106::parse(&life106::write(&pattern)).unwrap(), pattern);
+}
+
+#[test]
+fn parses_life105_blocks() {
+    let text = "#Life 1.05\n#D Glider\n#D two blocks\n#R 23/36\n#P -1 -1\n.*\n..*\n***\n#P 5 0\n*\n";
+    let pattern = life105::parse(text).unwrap();
+    assert_eq!(pattern.name.as_deref(), Some("Glider"));
+    assert_eq!(pattern.comments, vec!["two blocks".to_string()]);
+    assert_eq!(pattern.rule, Some(Rule::HIGHLIFE));
+    assert_eq!((pattern.width, pattern.height), (7, 3));
+    assert_eq!(pattern.cells, vec![(1, 0), (2, 1), (6, 1), (0, 2), (1, 2), (2, 2)]);
+}
+
+#[test]
+fn life105_round_trips() {
+    for rule in [Rule::CONWAY, Rule::SEEDS, Rule::DAY_AND_NIGHT] {
+        let mut pattern = random_pattern();
+        pattern.name = Some("soup".to_string());
+        pattern.comments = vec!["random".to_string()];
+        pattern.rule = Some(rule);
+        assert_eq!(life105::parse(&life105::write(&pattern)).unwrap(), pattern);
+    }
+}
+
+//坐标相距很远的 .lif 文件返回错误,不会溢出或在转换为棋盘时分配大量内存
+#[test]
+fn lif_rejects_distant_coordinates() {
+    let extreme = "#Life 1.06\n-9000000000000000000 0\n9000000000000000000 0\n";
+    assert!(matches!(life106::parse(extreme), Err(PatternError::OutOfRange)));
+    let distant = "#Life 1.06\n0 0\n3000000 3000000\n";
+    assert!(matches!(life106::parse(distant), Err(PatternError::OutOfRange)));
+
+    let extreme = "#Life 1.05\n#P -9000000000000000000 0\n*\n#P 9000000000000000000 0\n*\n";
+    assert!(matches!(life105::parse(extreme), Err(PatternError::OutOfRange)));
+    let distant = "#Life 1.05\n#P 0 0\n*\n#P 3000000 3000000\n*\n";
+    assert!(matches!(life105::parse(distant), Err(PatternError::OutOfRange)));
+    assert!(life105::parse("#Life 1.05\n#P 9223372036854775807 0\n.*\n").is_err());
+
+    //在范围内但面积很大的图样可以载入,但不能转换为稠密棋盘
+    let pattern = life106::parse("#Life 1.06\n0 0\n1000000 1000000\n").unwrap();
+    assert_eq!((pattern.width, pattern.height), (1000001, 1000001));
+    assert!(pattern::check_board_size(pattern.width, pattern.height).is_err());
+}
+
+#[test]
+fn detects_format_by_extension_and_content() {
+    let glider_106 = "#Life 1.06\n0 0\n";
+    let glider_105 = "#Life 1.05\n#P 0 0\n*\n";
+    assert_eq!(Format::detect(Path::new("a.rle"), ""), Some(Format::Rle));
+    assert_eq!(Format::detect(Path::new("a.cells"), ""), Some(Format::Plaintext));
+    assert_eq!(Format::detect(Path::new("a.lif"), glider_106), Some(Format::Life106));
+    assert_eq!(Format::detect(Path::new("a.LIF"), glider_105), Some(Format::Life105));
+
+    assert_eq!(Format::detect(Path::new("pattern"), GLIDER_RLE), Some(Format::Rle));
+    assert_eq!(Format::detect(Path::new("pattern.dat"), "!Name: x\n.O\n"), Some(Format::Plaintext));
+    assert_eq!(Format::sniff(glider_105), Some(Format::Life105));
+    assert_eq!(Format::sniff("hello world"), None);
+}
@@ -0,0 +1,117 @@
+use conway_wgpu::period::PeriodKind;
+use conway_wgpu::{Board, Engine, Period, PeriodDetector, Rule, SparseUniverse};
+
+//逐代演化并观察,返回最后一代的检测结果
+fn detect(engine: &mut dyn Engine, rule: &Rule, generations: u64) -> Option<Period> {
+    let mut detector = PeriodDetector::new();
+    let mut period = detector.observe_engine(0, engine);
+    for generation in 1..=generations {
+        engine.step(rule);
+        period = detector.observe_engine(generation, engine);
+    }
+    period
+}
+
+fn universe(cells: &[(i64, i64)]) -> SparseUniverse {
+    SparseUniverse::from_cells(cells.iter().copied())
+}
+
+#[test]
+fn block_is_still_life() {
+    let mut block = universe(&[(0, 0), (1, 0), (0, 1), (1, 1)]);
+    let period = detect(&mut block, &Rule::CONWAY, 3).unwrap();
+    assert_eq!(period, Period { start: 0, period: 1, dx: 0, dy: 0, population: 4 });
+    assert_eq!(period.kind(), PeriodKind::StillLife);
+    assert_eq!(period.to_string(), "静物");
+}
+
+#[test]
+fn blinker_and_pentadecathlon_are_oscillators() {
+    let mut blinker = universe(&[(-1, 0), (0, 0), (1, 0)]);
+    let period = detect(&mut blinker, &Rule::CONWAY, 5).unwrap();
+    assert_eq!((period.kind(), period.period, period.start), (PeriodKind::Oscillator, 2, 0));
+    assert_eq!(period.to_string(), "周期 2 的振荡子");
+
+    //一行 10 个细胞演化若干代后成为周期 15 的十五项振荡子
+    let row: Vec<_> = (0..10).map(|x| (x, 0)).collect();
+    let mut pentadecathlon = universe(&row);
+    let period = detect(&mut pentadecathlon, &Rule::CONWAY, 60).unwrap();
+    assert_eq!((period.kind(), period.period), (PeriodKind::Oscillator, 15));
+    assert!(period.start > 0);
+}
+
+//y 轴向上,滑翔机向右下移动,这个朝向的轻型飞船向左移动
+#[test]
+fn spaceships_report_velocity() {
+    let mut glider = universe(&[(1, 2), (2, 1), (0, 0), (1, 0), (2, 0)]);
+    let period = detect(&mut glider, &Rule::CONWAY, 8).unwrap();
+    assert_eq!(period, Period { start: 0, period: 4, dx: 1, dy: -1, population: 5 });
+    assert_eq!(period.kind(), PeriodKind::Spaceship);
+    assert_eq!(period.to_string(), "周期 4 的飞船,速度 (1, -1)/4");
+
+    let mut lwss = universe(&[(1, 3), (4, 3), (0, 2), (0, 1), (4, 1), (0, 0), (1, 0), (2, 0), (3, 0)]);
+    let period = detect(&mut lwss, &Rule::CONWAY, 12).unwrap();
+    assert_eq!((period.period, period.dx, period.dy), (4, -2, 0));
+}
+
+#[test]
+fn extinction() {
+    let mut board = Board::new(5, 5);
+    board.set(2, 2, true);
+    let period = detect(&mut board, &Rule::CONWAY, 2).unwrap();
+    assert_eq!((period.kind(), period.start, period.population), (PeriodKind::Extinct, 1, 0));
+    assert_eq!(period.to_string(), "全部死亡");
+}
+
+//活细胞的顺序与平移不影响形状的比较
+#[test]
+fn translation_and_order_are_ignored() {
+    let mut detector = PeriodDetector::new();
+    assert_eq!(detector.observe(0, &[(5, 5), (6, 5), (5, 6)]), None);
+    let period = detector.observe(1, &[(-9, 1), (-10, 2), (-10, 1)]).unwrap();
+    assert_eq!((period.period, period.dx, period.dy), (1, -15, -4));
+}
+
+//代数不连续时重新开始,不会把相隔多代的两代当作周期
+#[test]
+fn gaps_reset_history() {
+    let mut detector = PeriodDetector::new();
+    assert_eq!(detector.observe(0, &[(0, 0)]), None);
+    assert_eq!(detector.observe(2, &[(0, 0)]), None);
+    assert!(detector.observe(3, &[(0, 0)]).is_some());
+}
+
+//离开循环后不再返回周期,再次进入时从新的循环开始计算
+#[test]
+fn leaving_and_reentering_cycles() {
+    let mut detector = PeriodDetector::new();
+    let shapes: [&[(i64, i64)]; 6] = [
+        &[(0, 0)],
+        &[(0, 0)],
+        &[(0, 0), (1, 0)],
+        &[(0, 0), (0, 1), (0, 2)],
+        &[(0, 0), (1, 0), (2, 0)],
+        &[(0, 0), (0, 1), (0, 2)],
+    ];
+    let results: Vec<_> = shapes.iter().enumerate().map(|(i, cells)| detector.observe(i as u64, cells)).collect();
+    assert_eq!(results[1].map(|p| (p.start, p.period)), Some((0, 1)));
+    assert_eq!(results[2], None);
+    assert_eq!(results[3], None);
+    //横竖两种摆放不是同一个形状
+    assert_eq!(results[4], None);
+    assert_eq!(results[5].map(|p| (p.start, p.period)), Some((3, 2)));
+}
+
+//周期超过记住的代数时检测不到
+#[test]
+fn history_limits_period() {
+    let mut blinker = universe(&[(-1, 0), (0, 0), (1, 0)]);
+    let mut short = PeriodDetector::with_history(1);
+    let mut long = PeriodDetector::with_history(2);
+    for generation in 0..6 {
+        assert_eq!(short.observe_engine(generation, &blinker), None);
+        let period = long.observe_engine(generation, &blinker);
+        assert_eq!(period.is_some(), generation >= 2);
+        blinker.step(&Rule::CONWAY);
+    }
+}
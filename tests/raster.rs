@@ -0,0 +1,75 @@
+use conway_wgpu::raster::{parse_color, rasterize, ColorScheme, RasterOptions};
+use conway_wgpu::Board;
+
+fn pixel(rgba: &[u8], width: u32, x: u32, y: u32) -> [u8; 4] {
+    let i = ((y * width + x) * 4) as usize;
+    [rgba[i], rgba[i + 1], rgba[i + 2], rgba[i + 3]]
+}
+
+//棋盘最上面一行是图片的第一行,每个细胞占 cell_size x cell_size 像素
+#[test]
+fn cells_fill_squares_top_row_first() {
+    let mut board = Board::new(3, 2);
+    board.set(0, 1, true);
+    board.set(2, 0, true);
+    let colors = ColorScheme { alive: [200, 100, 50], dead: [1, 2, 3], ..ColorScheme::default() };
+    let options = RasterOptions { cell_size: 2, grid_lines: false, colors };
+    assert_eq!(options.image_size(&board), (6, 4));
+
+    let rgba = rasterize(&board, &options);
+    assert_eq!(rgba.len(), 6 * 4 * 4);
+    for py in 0..4 {
+        for px in 0..6 {
+            let alive = board.get((px / 2) as usize, 1 - (py / 2) as usize);
+            let [r, g, b] = if alive { colors.alive } else { colors.dead };
+            assert_eq!(pixel(&rgba, 6, px, py), [r, g, b, u8::MAX], "({}, {})", px, py);
+        }
+    }
+}
+
+//网格线位于每个细胞最左边一列与最下面一行像素,颜色在线性空间中与灰色各占一半
+#[test]
+fn grid_lines_on_left_and_bottom_edges() {
+    let mut board = Board::new(2, 2);
+    board.set(0, 0, true);
+    let options = RasterOptions { cell_size: 5, grid_lines: true, ..RasterOptions::default() };
+    let rgba = rasterize(&board, &options);
+
+    //左下角的细胞是活细胞,图片中位于第 5 到 9 行、第 0 到 4 列
+    assert_eq!(pixel(&rgba, 10, 2, 7), [255, 255, 255, 255]);
+    assert_eq!(pixel(&rgba, 10, 0, 7), [207, 207, 207, 255]);
+    assert_eq!(pixel(&rgba, 10, 2, 9), [207, 207, 207, 255]);
+    assert_eq!(pixel(&rgba, 10, 7, 2), [0, 0, 0, 255]);
+    assert_eq!(pixel(&rgba, 10, 5, 2), [99, 99, 99, 255]);
+    assert_eq!(pixel(&rgba, 10, 7, 4), [99, 99, 99, 255]);
+    assert_eq!(pixel(&rgba, 10, 9, 3), [0, 0, 0, 255]);
+}
+
+//细胞不超过 4 像素时不画网格线
+#[test]
+fn small_cells_have_no_grid_lines() {
+    let board = Board::random(6, 6);
+    for cell_size in 1..=4 {
+        let plain = RasterOptions { cell_size, ..RasterOptions::default() };
+        let lined = RasterOptions { grid_lines: true, ..plain };
+        assert_eq!(rasterize(&board, &plain), rasterize(&board, &lined));
+    }
+}
+
+#[test]
+fn zero_cell_size_is_one_pixel() {
+    let board = Board::random(4, 3);
+    let zero = RasterOptions { cell_size: 0, ..RasterOptions::default() };
+    let one = RasterOptions { cell_size: 1, ..RasterOptions::default() };
+    assert_eq!(zero.image_size(&board), (4, 3));
+    assert_eq!(rasterize(&board, &zero), rasterize(&board, &one));
+}
+
+#[test]
+fn parse_colors() {
+    assert_eq!(parse_color("ff8000"), Some([255, 128, 0]));
+    assert_eq!(parse_color("#0A0b0C"), Some([10, 11, 12]));
+    assert_eq!(parse_color("fff"), None);
+    assert_eq!(parse_color("gg0000"), None);
+    assert_eq!(parse_color("ff00ff00"), None);
+}
@@ -0,0 +1,180 @@
+use std::path::Path;
+
+use conway_wgpu::raster::{ColorScheme, RasterOptions};
+use conway_wgpu::record::{AnimationWriter, RecordError, RecordFormat, RecordOptions, Recorder};
+use conway_wgpu::{Board, Rule};
+
+//滑翔机演化若干代,每代录制一帧
+fn record_glider(options: RecordOptions, frames: usize) -> (Recorder, Vec<Board>) {
+    let mut board = Board::new(8, 6);
+    for (x, y) in [(1, 5), (2, 4), (0, 3), (1, 3), (2, 3)] {
+        board.set(x, y, true);
+    }
+    let mut recorder = Recorder::new(options);
+    let mut boards = Vec::new();
+    for _ in 0..frames {
+        recorder.push_board(&board).unwrap();
+        boards.push(board.clone());
+        board.step(&Rule::CONWAY);
+    }
+    (recorder, boards)
+}
+
+//画面第一行对应棋盘最上面一行,每个细胞占 scale x scale 像素
+fn expected_pixel(board: &Board, options: &RecordOptions, px: u32, py: u32) -> [u8; 3] {
+    let scale = options.raster.cell_size;
+    let (x, y) = ((px / scale) as usize, board.height() - 1 - (py / scale) as usize);
+    if board.get(x, y) {
+        options.raster.colors.alive
+    } else {
+        options.raster.colors.dead
+    }
+}
+
+#[test]
+fn gif_round_trip() {
+    let options = RecordOptions {
+        delay_ms: 50,
+        raster: RasterOptions {
+            cell_size: 3,
+            grid_lines: false,
+            colors: ColorScheme { alive: [255, 200, 0], dead: [10, 20, 30], ..ColorScheme::default() },
+        },
+    };
+    let (recorder, boards) = record_glider(options, 5);
+    assert_eq!(recorder.size(), Some((24, 18)));
+    let mut bytes = Vec::new();
+    recorder.encode(&mut bytes, RecordFormat::Gif).unwrap();
+
+    let mut decode_options = gif::DecodeOptions::new();
+    decode_options.set_color_output(gif::ColorOutput::RGBA);
+    let mut decoder = decode_options.read_info(bytes.as_slice()).unwrap();
+    assert_eq!((decoder.width(), decoder.height()), (24, 18));
+    let mut count = 0;
+    while let Some(frame) = decoder.read_next_frame().unwrap() {
+        let board = &boards[count];
+        assert_eq!(frame.delay, 5);
+        for (i, pixel) in frame.buffer.chunks(4).enumerate() {
+            let (px, py) = (i as u32 % 24, i as u32 / 24);
+            assert_eq!(pixel[..3], expected_pixel(board, &options, px, py), "第 {} 帧 ({}, {})", count, px, py);
+        }
+        count += 1;
+    }
+    assert_eq!(count, 5);
+}
+
+#[test]
+fn apng_round_trip() {
+    let options = RecordOptions { delay_ms: 40, ..RecordOptions::default() };
+    let (recorder, boards) = record_glider(options, 4);
+    let mut bytes = Vec::new();
+    recorder.encode(&mut bytes, RecordFormat::Apng).unwrap();
+
+    let mut reader = png::Decoder::new(bytes.as_slice()).read_info().unwrap();
+    let animation = reader.info().animation_control().copied().unwrap();
+    assert_eq!((animation.num_frames, animation.num_plays), (4, 0));
+    let mut decoded = vec![0; reader.output_buffer_size()];
+    for board in &boards {
+        let info = reader.next_frame(&mut decoded).unwrap();
+        assert_eq!((info.width, info.height), (32, 24));
+        let control = reader.info().frame_control().copied().unwrap();
+        assert_eq!((control.delay_num, control.delay_den), (40, 1000));
+        for (i, pixel) in decoded[..info.buffer_size()].chunks(4).enumerate() {
+            let (px, py) = (i as u32 % 32, i as u32 / 32);
+            assert_eq!(pixel[..3], expected_pixel(board, &options, px, py));
+            assert_eq!(pixel[3], u8::MAX);
+        }
+    }
+}
+
+//颜色超过 256 种时量化后仍然能编码
+#[test]
+fn gif_quantizes_many_colors() {
+    let mut recorder = Recorder::new(RecordOptions::default());
+    let rgba: Vec<u8> = (0..32 * 32u32).flat_map(|i| [i as u8, (i >> 2) as u8, (i * 7) as u8, u8::MAX]).collect();
+    recorder.push_rgba(32, 32, rgba).unwrap();
+    let mut bytes = Vec::new();
+    recorder.encode(&mut bytes, RecordFormat::Gif).unwrap();
+
+    let mut decoder = gif::DecodeOptions::new().read_info(bytes.as_slice()).unwrap();
+    assert!(decoder.read_next_frame().unwrap().is_some());
+    assert!(decoder.read_next_frame().unwrap().is_none());
+}
+
+//逐帧编码的结果与先保存所有帧再编码完全一致
+#[test]
+fn streaming_matches_recorder() {
+    let options = RecordOptions { delay_ms: 30, ..RecordOptions::default() };
+    let (recorder, boards) = record_glider(options, 6);
+    for format in [RecordFormat::Gif, RecordFormat::Apng] {
+        let mut buffered = Vec::new();
+        recorder.encode(&mut buffered, format).unwrap();
+
+        let mut streamed = Vec::new();
+        let mut writer = AnimationWriter::new(&mut streamed, format, 32, 24, boards.len() as u32, options).unwrap();
+        for board in &boards {
+            writer.write_board(board).unwrap();
+        }
+        assert_eq!(writer.frame_count(), 6);
+        writer.finish().unwrap();
+        assert_eq!(streamed, buffered, "{:?}", format);
+    }
+}
+
+#[test]
+fn streaming_rejects_wrong_sizes_and_frame_counts() {
+    let options = RecordOptions::default();
+    let mut writer = AnimationWriter::new(Vec::new(), RecordFormat::Gif, 32, 24, 1, options).unwrap();
+    assert!(matches!(writer.write_board(&Board::new(8, 8)), Err(RecordError::SizeMismatch { .. })));
+    assert_eq!(writer.frame_count(), 0);
+    assert!(matches!(
+        AnimationWriter::new(Vec::new(), RecordFormat::Gif, 70000, 1, 1, options),
+        Err(RecordError::TooLarge { .. })
+    ));
+    //写入的帧数与事先声明的帧数不一致时失败
+    let writer = AnimationWriter::new(Vec::new(), RecordFormat::Apng, 4, 4, 2, options).unwrap();
+    assert!(matches!(writer.finish(), Err(RecordError::FrameCount { expected: 2, actual: 0 })));
+    let mut writer = AnimationWriter::new(Vec::new(), RecordFormat::Apng, 4, 4, 1, options).unwrap();
+    writer.write_rgba(4, 4, &[0; 64]).unwrap();
+    assert!(matches!(writer.write_rgba(4, 4, &[0; 64]), Err(RecordError::FrameCount { expected: 1, actual: 2 })));
+}
+
+#[test]
+fn rejects_frames_of_different_size() {
+    let mut recorder = Recorder::new(RecordOptions::default());
+    recorder.push_rgba(2, 2, vec![0; 16]).unwrap();
+    match recorder.push_rgba(3, 2, vec![0; 24]) {
+        Err(RecordError::SizeMismatch { expected, actual }) => assert_eq!((expected, actual), ((2, 2), (3, 2))),
+        other => panic!("应该因为尺寸不一致而失败: {:?}", other),
+    }
+    assert_eq!(recorder.frame_count(), 1);
+}
+
+//达到内存上限后拒绝新的帧,已经录制的帧仍然可以保存
+#[test]
+fn rejects_frames_over_memory_limit() {
+    let mut recorder = Recorder::new(RecordOptions::default());
+    recorder.set_memory_limit(40);
+    recorder.push_rgba(2, 2, vec![0; 16]).unwrap();
+    recorder.push_rgba(2, 2, vec![255; 16]).unwrap();
+    assert!(matches!(recorder.push_rgba(2, 2, vec![0; 16]), Err(RecordError::MemoryLimit { frames: 2 })));
+    assert_eq!(recorder.frame_count(), 2);
+    assert!(recorder.encode(Vec::new(), RecordFormat::Apng).is_ok());
+}
+
+#[test]
+fn rejects_empty_recording_and_unknown_format() {
+    let recorder = Recorder::new(RecordOptions::default());
+    assert!(matches!(recorder.encode(Vec::new(), RecordFormat::Gif), Err(RecordError::NoFrames)));
+    assert!(matches!(recorder.save(Path::new("conway.txt")), Err(RecordError::UnknownFormat(_))));
+}
+
+#[test]
+fn format_from_extension() {
+    assert_eq!(RecordFormat::from_path(Path::new("a.gif")), Some(RecordFormat::Gif));
+    assert_eq!(RecordFormat::from_path(Path::new("a.GIF")), Some(RecordFormat::Gif));
+    assert_eq!(RecordFormat::from_path(Path::new("a.png")), Some(RecordFormat::Apng));
+    assert_eq!(RecordFormat::from_path(Path::new("a.apng")), Some(RecordFormat::Apng));
+    assert_eq!(RecordFormat::from_path(Path::new("a.rle")), None);
+    assert_eq!(RecordFormat::from_path(Path::new("gif")), None);
+}
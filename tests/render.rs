@@ -0,0 +1,35 @@
+use conway_wgpu::render::{cell_instance, live_instances};
+use conway_wgpu::Board;
+
+#[test]
+fn only_live_cells_become_instances() {
+    let mut board = Board::new(7, 5);
+    assert!(live_instances(&board).is_empty());
+
+    board.set(0, 0, true);
+    board.set(3, 2, true);
+    board.set(6, 4, true);
+    let instances = live_instances(&board);
+    assert_eq!(instances.len(), board.population());
+
+    let expected: Vec<_> = [(0, 0), (3, 2), (6, 4)]
+        .iter()
+        .map(|&(x, y)| cell_instance(x, y).to_raw().model)
+        .collect();
+    let actual: Vec<_> = instances.iter().map(|raw| raw.model).collect();
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn random_board_instance_count_matches_population() {
+    let board = Board::random(64, 48);
+    assert_eq!(live_instances(&board).len(), board.population());
+}
+
+#[test]
+fn cell_instances_are_placed_at_cell_centers() {
+    for (x, y) in [(0, 0), (6, 4), (3, 2)] {
+        let instance = cell_instance(x, y);
+        assert_eq!((instance.position.x, instance.position.y), (x as f32 + 0.5, y as f32 + 0.5));
+    }
+}
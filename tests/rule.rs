@@ -0,0 +1,41 @@
+use conway_wgpu::{Rule, RuleParseError};
+
+fn parse(s: &str) -> Result<Rule, RuleParseError> {
+    s.parse()
+}
+
+#[test]
+fn parses_standard_notation() {
+    assert_eq!(parse("B3/S23"), Ok(Rule::CONWAY));
+    assert_eq!(parse("b36/s23"), Ok(Rule::HIGHLIFE));
+    assert_eq!(parse("B2/S"), Ok(Rule::SEEDS));
+    assert_eq!(parse("B3678/S34678"), Ok(Rule::DAY_AND_NIGHT));
+    assert_eq!(parse("S23/B3"), Ok(Rule::CONWAY));
+    assert_eq!(parse("B3S23"), Ok(Rule::CONWAY));
+}
+
+#[test]
+fn parses_survival_birth_notation() {
+    assert_eq!(parse("23/3"), Ok(Rule::CONWAY));
+    assert_eq!(parse("23/36"), Ok(Rule::HIGHLIFE));
+    assert_eq!(parse("/2"), Ok(Rule::SEEDS));
+}
+
+#[test]
+fn display_round_trips() {
+    for rule in Rule::PRESETS {
+        assert_eq!(parse(&rule.to_string()), Ok(*rule));
+    }
+    assert_eq!(Rule::SEEDS.to_string(), "B2/S");
+}
+
+#[test]
+fn reports_parse_errors() {
+    assert_eq!(parse(""), Err(RuleParseError::Empty));
+    assert_eq!(parse("B39/S23"), Err(RuleParseError::InvalidCount('9')));
+    assert_eq!(parse("B3/S2x"), Err(RuleParseError::InvalidChar('x')));
+    assert_eq!(parse("B3/B23"), Err(RuleParseError::DuplicateSection('B')));
+    assert_eq!(parse("B3"), Err(RuleParseError::MissingSection('S')));
+    assert_eq!(parse("3B/S23"), Err(RuleParseError::MissingPrefix));
+    assert_eq!(parse("233"), Err(RuleParseError::MissingSlash));
+}
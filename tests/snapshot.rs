@@ -0,0 +1,62 @@
+use conway_wgpu::raster::{rasterize, RasterOptions};
+use conway_wgpu::snapshot::{self, encode_png};
+use conway_wgpu::Board;
+
+mod common;
+
+//CPU 光栅化的结果作为基准图片
+fn expected_image(board: &Board, cell_size: u32, grid_lines: bool) -> Vec<u8> {
+    rasterize(board, &RasterOptions { cell_size, grid_lines, ..RasterOptions::default() })
+}
+
+#[test]
+fn png_round_trip() {
+    let board = Board::random(7, 5);
+    let rgba = expected_image(&board, 2, false);
+    let mut bytes = Vec::new();
+    encode_png(&mut bytes, 14, 10, &rgba).unwrap();
+
+    let decoder = png::Decoder::new(bytes.as_slice());
+    let mut reader = decoder.read_info().unwrap();
+    let mut decoded = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut decoded).unwrap();
+    assert_eq!((info.width, info.height), (14, 10));
+    assert_eq!(info.color_type, png::ColorType::Rgba);
+    assert_eq!(&decoded[..info.buffer_size()], rgba.as_slice());
+}
+
+#[test]
+fn png_rejects_wrong_buffer_size() {
+    assert!(encode_png(Vec::new(), 4, 4, &[0; 12]).is_err());
+}
+
+//离屏渲染的结果与 CPU 光栅化的基准图片完全一致
+#[test]
+fn render_board_matches_golden_image() {
+    let (device, queue) = match common::device() {
+        Some(device) => device,
+        None => return,
+    };
+    //非正方形、宽度不是 64 的整数倍,读回时需要处理每行的对齐
+    for (board, cell_size) in [(Board::random(13, 7), 3), (Board::random(40, 90), 1), (Board::random(5, 5), 8)] {
+        let rgba = snapshot::render_board(&device, &queue, &board, cell_size, false);
+        assert_eq!(rgba, expected_image(&board, cell_size, false), "{}x{} 每个细胞 {} 像素", board.width(), board.height(), cell_size);
+    }
+}
+
+//网格线的位置一致,颜色经过 sRGB 换算,允许相差 1
+#[test]
+fn render_board_grid_lines_match_raster() {
+    let (device, queue) = match common::device() {
+        Some(device) => device,
+        None => return,
+    };
+    for (board, cell_size) in [(Board::random(9, 6), 8), (Board::random(4, 4), 4)] {
+        let rgba = snapshot::render_board(&device, &queue, &board, cell_size, true);
+        let expected = expected_image(&board, cell_size, true);
+        assert_eq!(rgba.len(), expected.len());
+        for (i, (&actual, &expected)) in rgba.iter().zip(&expected).enumerate() {
+            assert!(actual.abs_diff(expected) <= 1, "每个细胞 {} 像素, 第 {} 字节: {} != {}", cell_size, i, actual, expected);
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use std::collections::HashSet;
+
+use conway_wgpu::pattern::Format;
+use conway_wgpu::{Board, Engine, Rule, SparseUniverse};
+
+const GOSPER_GUN: &str = "\
+#N Gosper glider gun
+x = 36, y = 9, rule = B3/S23
+24bo$22bobo$12b2o6b2o12b2o$11bo3bo4b2o12b2o$2o8bo5bo3b2o$2o8bo3bob2o4b
+obo$10bo5bo7bo$11bo3bo$12b2o!";
+
+fn live_set(engine: &dyn Engine) -> HashSet<(i64, i64)> {
+    engine.live_cells().into_iter().collect()
+}
+
+//随机图样放在大棋盘中央,演化的代数不足以让细胞到达边界,两种引擎的结果应当完全一致
+#[test]
+fn matches_dense_board_away_from_edges() {
+    for rule in Rule::PRESETS {
+        let soup = Board::random(30, 30);
+        let mut board = Board::new(150, 150);
+        for (x, y) in soup.live_cells() {
+            board.set(x as usize + 60, y as usize + 60, true);
+        }
+        let mut universe = SparseUniverse::from_board(&board);
+
+        for generation in 1..=50 {
+            board.step(rule);
+            universe.step(rule);
+            assert_eq!(live_set(&board), live_set(&universe), "{} 第 {} 代不一致", rule, generation);
+        }
+    }
+}
+
+#[test]
+fn glider_gun_grows_without_bounds() {
+    let gun = Format::Rle.parse(GOSPER_GUN).unwrap();
+    let mut universe = SparseUniverse::from_board(&gun.to_board());
+    assert_eq!(universe.population(), 36);
+
+    //每 30 代发射一架滑翔机
+    for _ in 0..600 {
+        universe.step(&Rule::CONWAY);
+    }
+    assert_eq!(universe.population(), 36 + 20 * 5);
+    let (min_x, min_y, max_x, _) = universe.bounding_box().unwrap();
+    //滑翔机飞出了图样原本的范围,向右下方移动
+    assert!(max_x > 150 && min_y < -140);
+    assert_eq!(min_x, 0);
+}
+
+#[test]
+fn set_get_and_negative_coordinates() {
+    let mut universe = SparseUniverse::new();
+    universe.set(-5, -7, true);
+    universe.set(3, 4, true);
+    assert!(universe.get(-5, -7));
+    assert_eq!(universe.population(), 2);
+    assert_eq!(universe.bounding_box(), Some((-5, -7, 3, 4)));
+
+    universe.set(-5, -7, false);
+    assert!(!universe.get(-5, -7));
+    assert_eq!(universe.live_cells(), vec![(3, 4)]);
+}
+
+#[test]
+fn isolated_cells_survive_with_s0() {
+    let rule: Rule = "B3/S0".parse().unwrap();
+    let mut universe = SparseUniverse::from_cells([(0, 0), (10, 10)]);
+    universe.step(&rule);
+    assert_eq!(universe.population(), 2);
+}
+
+#[test]
+fn to_board_copies_region() {
+    let universe = SparseUniverse::from_cells([(-1, -1), (0, 0), (2, 1), (5, 5)]);
+    let board = universe.to_board(-1, -1, 4, 3);
+    assert_eq!(board.population(), 3);
+    assert!(board.get(0, 0) && board.get(1, 1) && board.get(3, 2));
+}
+
+#[test]
+fn pattern_round_trip() {
+    let universe = SparseUniverse::from_cells([(-3, 2), (-2, 1), (-4, 0), (-3, 0), (-2, 0)]);
+    let pattern = universe.to_pattern();
+    assert_eq!((pattern.width, pattern.height), (3, 3));
+    let board = pattern.to_board();
+    assert_eq!(board.population(), 5);
+    //图样的第一行是最上面一行
+    assert!(board.get(1, 2));
+}
@@ -0,0 +1,121 @@
+use std::collections::HashSet;
+
+use conway_wgpu::stats::StatsFormat;
+use conway_wgpu::{Board, Engine, GenerationStats, Rule, SparseUniverse, Statistics};
+
+fn live_set(engine: &dyn Engine) -> HashSet<(i64, i64)> {
+    engine.live_cells().into_iter().collect()
+}
+
+//出生与死亡的数量与逐代比较活细胞集合的结果一致,种群守恒
+#[test]
+fn births_and_deaths_match_set_difference() {
+    let mut board = Board::random(40, 30);
+    let mut statistics = Statistics::new();
+    let first = statistics.observe_engine(0, &board);
+    assert_eq!((first.births, first.deaths), (None, None));
+
+    for generation in 1..=30 {
+        let before = live_set(&board);
+        board.step(&Rule::CONWAY);
+        let after = live_set(&board);
+        let stats = statistics.observe_engine(generation, &board);
+        assert_eq!(stats.population, board.population());
+        assert_eq!(stats.births, Some(after.difference(&before).count()), "第 {} 代", generation);
+        assert_eq!(stats.deaths, Some(before.difference(&after).count()), "第 {} 代", generation);
+        assert_eq!(stats.bounding_box, Engine::bounding_box(&board));
+    }
+    assert_eq!(statistics.history().len(), 31);
+    assert_eq!(statistics.latest().map(|stats| stats.generation), Some(30));
+}
+
+#[test]
+fn blinker_statistics() {
+    let mut blinker = SparseUniverse::from_cells([(-1, 0), (0, 0), (1, 0)]);
+    let mut statistics = Statistics::new();
+    statistics.observe_engine(0, &blinker);
+    blinker.step(&Rule::CONWAY);
+    let stats = statistics.observe_engine(1, &blinker);
+    assert_eq!(
+        stats,
+        GenerationStats { generation: 1, population: 3, births: Some(2), deaths: Some(2), bounding_box: Some((0, -1, 0, 1)) }
+    );
+}
+
+//代数不连续或棋盘被修改后不统计出生与死亡
+#[test]
+fn gaps_and_edits_have_no_changes() {
+    let mut statistics = Statistics::new();
+    statistics.observe(0, &[(0, 0)]);
+    assert_eq!(statistics.observe(2, &[(0, 0)]).births, None);
+    assert_eq!(statistics.observe(3, &[(0, 0), (1, 1)]).births, Some(1));
+    statistics.edited();
+    assert_eq!(statistics.observe(4, &[]).deaths, None);
+    assert_eq!(statistics.latest().unwrap().bounding_box, None);
+}
+
+//只记录种群时不需要活细胞,之后的一代也没有出生与死亡
+#[test]
+fn population_only_observation() {
+    let universe = SparseUniverse::from_cells([(5, 5), (7, 9)]);
+    let mut statistics = Statistics::new();
+    statistics.observe_engine(0, &universe);
+    let stats = statistics.observe_population(1, &universe);
+    assert_eq!((stats.population, stats.births, stats.bounding_box), (2, None, Some((5, 5, 7, 9))));
+    assert_eq!(statistics.observe_engine(2, &universe).births, None);
+}
+
+//只有种群数量时没有包围盒,也不影响之后的出生与死亡统计
+#[test]
+fn count_only_observation() {
+    let mut statistics = Statistics::new();
+    statistics.observe(0, &[(0, 0)]);
+    let stats = statistics.observe_count(1, 7);
+    assert_eq!((stats.population, stats.births, stats.bounding_box), (7, None, None));
+    assert_eq!(statistics.observe(2, &[(0, 0)]).births, None);
+}
+
+#[test]
+fn history_limit_drops_oldest() {
+    let mut statistics = Statistics::with_history(3);
+    for generation in 0..5 {
+        statistics.observe(generation, &[(0, 0)]);
+    }
+    let generations: Vec<_> = statistics.history().iter().map(|stats| stats.generation).collect();
+    assert_eq!(generations, vec![2, 3, 4]);
+    statistics.clear();
+    assert!(statistics.latest().is_none());
+}
+
+#[test]
+fn csv_and_json_output() {
+    let mut statistics = Statistics::new();
+    statistics.observe(0, &[(0, 0), (2, -1)]);
+    statistics.observe(1, &[]);
+
+    let mut csv = Vec::new();
+    statistics.write_csv(&mut csv).unwrap();
+    assert_eq!(
+        String::from_utf8(csv).unwrap(),
+        "generation,population,births,deaths,min_x,min_y,max_x,max_y\n0,2,,,0,-1,2,0\n1,0,0,2,,,,\n"
+    );
+
+    let mut json = Vec::new();
+    statistics.write_json(&mut json).unwrap();
+    assert_eq!(
+        String::from_utf8(json).unwrap(),
+        "[\n  {\"generation\": 0, \"population\": 2, \"births\": null, \"deaths\": null, \"bounding_box\": [0, -1, 2, 0]},\n  {\"generation\": 1, \"population\": 0, \"births\": 0, \"deaths\": 2, \"bounding_box\": null}\n]\n"
+    );
+
+    let mut empty = Vec::new();
+    Statistics::new().write_json(&mut empty).unwrap();
+    assert_eq!(String::from_utf8(empty).unwrap(), "[\n]\n");
+}
+
+#[test]
+fn format_from_extension() {
+    assert_eq!(StatsFormat::from_path("a.csv".as_ref()), Some(StatsFormat::Csv));
+    assert_eq!(StatsFormat::from_path("a.JSON".as_ref()), Some(StatsFormat::Json));
+    assert_eq!(StatsFormat::from_path("a.txt".as_ref()), None);
+    assert!(Statistics::new().save("stats.txt".as_ref()).is_err());
+}
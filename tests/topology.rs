@@ -0,0 +1,73 @@
+use conway_wgpu::{Board, Rule, Topology};
+
+// 向右上方移动的滑翔机(y 轴向上)
+const GLIDER: [(usize, usize); 5] = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+
+fn glider_board(size: usize, topology: Topology) -> Board {
+    let mut board = Board::new(size, size).with_topology(topology);
+    for &(x, y) in &GLIDER {
+        board.set(x, y, true);
+    }
+    board
+}
+
+#[test]
+fn glider_survives_indefinitely_on_torus() {
+    let start = glider_board(8, Topology::Torus);
+    let mut board = start.clone();
+    // 滑翔机每 4 代移动一格,8x8 环面上 32 代回到原位
+    for generation in 1..=32 * 50 {
+        board.step(&Rule::CONWAY);
+        assert_eq!(board.population(), 5, "第 {} 代滑翔机被破坏", generation);
+        if generation % 32 == 0 {
+            assert_eq!(board, start);
+        }
+    }
+}
+
+#[test]
+fn glider_dies_at_bounded_edges() {
+    let mut board = glider_board(8, Topology::Bounded);
+    for _ in 0..64 {
+        board.step(&Rule::CONWAY);
+    }
+    // 撞到角落后变成方块
+    assert_eq!(board.population(), 4);
+}
+
+#[test]
+fn glider_survives_on_klein_bottle() {
+    let mut board = glider_board(8, Topology::KleinBottle);
+    for generation in 1..=32 * 20 {
+        board.step(&Rule::CONWAY);
+        assert_eq!(board.population(), 5, "第 {} 代滑翔机被破坏", generation);
+    }
+}
+
+#[test]
+fn wrap_maps_edges() {
+    let (w, h) = (5, 4);
+    assert_eq!(Topology::Bounded.wrap(-1, 0, w, h), None);
+    assert_eq!(Topology::Bounded.wrap(2, 3, w, h), Some((2, 3)));
+
+    assert_eq!(Topology::Torus.wrap(-1, 0, w, h), Some((4, 0)));
+    assert_eq!(Topology::Torus.wrap(5, 4, w, h), Some((0, 0)));
+
+    // 左右直接相连,上下越界时 x 翻转
+    assert_eq!(Topology::KleinBottle.wrap(-1, 1, w, h), Some((4, 1)));
+    assert_eq!(Topology::KleinBottle.wrap(1, 4, w, h), Some((3, 0)));
+    assert_eq!(Topology::KleinBottle.wrap(-1, -1, w, h), Some((0, 3)));
+
+    // 两个方向越界时都翻转另一个方向
+    assert_eq!(Topology::CrossSurface.wrap(-1, 1, w, h), Some((4, 2)));
+    assert_eq!(Topology::CrossSurface.wrap(1, -1, w, h), Some((3, 3)));
+    assert_eq!(Topology::CrossSurface.wrap(-1, -1, w, h), None);
+}
+
+#[test]
+fn parses_topology_names() {
+    for topology in Topology::ALL {
+        assert_eq!(topology.to_string().parse::<Topology>(), Ok(*topology));
+    }
+    assert!("sphere".parse::<Topology>().is_err());
+}
@@ -0,0 +1,134 @@
+use cgmath::{Matrix4, Vector4};
+use conway_wgpu::render::cell_instance;
+use conway_wgpu::view::{View, MAX_CELL_SIZE, MIN_ZOOM};
+use winit::dpi::{LogicalSize, PhysicalPosition, PhysicalSize};
+
+const BOARDS: &[(usize, usize)] = &[(20, 20), (37, 23), (8, 64), (256, 144)];
+const WINDOWS: &[(f64, f64)] = &[(500.0, 500.0), (500.0, 311.0), (1280.0, 720.0), (300.0, 900.0), (63.0, 47.0)];
+const SCALE_FACTORS: &[f64] = &[1.0, 1.25, 1.5, 2.0, 3.0];
+
+//按窗口的逻辑尺寸与缩放比例创建 View,与 winit 报告的物理尺寸一致
+fn view(board: (usize, usize), window: (f64, f64), scale_factor: f64) -> View {
+    let size: PhysicalSize<u32> = LogicalSize::new(window.0, window.1).to_physical(scale_factor);
+    View::new(board.0, board.1, size.width, size.height)
+}
+
+//细胞中心经过实例矩阵与相机矩阵后的屏幕位置,与顶点着色器的计算一致
+fn rendered_center(view: &View, x: usize, y: usize) -> (f64, f64) {
+    let model: Matrix4<f32> = cell_instance(x, y).to_raw().model.into();
+    let clip = view.view_proj() * model * Vector4::new(0.0, 0.0, 0.0, 1.0);
+    view.clip_to_screen(clip.x / clip.w, clip.y / clip.w)
+}
+
+fn assert_picks_rendered_cells(view: &View, board: (usize, usize), scale_factor: f64) {
+    for y in 0..board.1 {
+        for x in 0..board.0 {
+            let (sx, sy) = rendered_center(view, x, y);
+            //光标位置由逻辑坐标换算得到
+            let cursor: PhysicalPosition<f64> =
+                PhysicalPosition::new(sx, sy).to_logical::<f64>(scale_factor).to_physical(scale_factor);
+            assert_eq!(
+                view.screen_to_cell(cursor.x, cursor.y),
+                (x as isize, y as isize),
+                "棋盘 {:?} 视图 {:?} 缩放 {}",
+                board,
+                view,
+                scale_factor
+            );
+        }
+    }
+}
+
+#[test]
+fn picking_rendered_cell_returns_that_cell() {
+    for &board in BOARDS {
+        for &window in WINDOWS {
+            for &scale_factor in SCALE_FACTORS {
+                assert_picks_rendered_cells(&view(board, window, scale_factor), board, scale_factor);
+            }
+        }
+    }
+}
+
+#[test]
+fn picking_follows_zoom_and_pan() {
+    for &board in BOARDS {
+        for &scale_factor in SCALE_FACTORS {
+            let mut view = view(board, (640.0, 480.0), scale_factor);
+            view.zoom_at(100.0, 70.0, 3.7);
+            view.pan(-33.0, 18.5);
+            assert_picks_rendered_cells(&view, board, scale_factor);
+        }
+    }
+}
+
+#[test]
+fn cell_centers_match_rendered_positions() {
+    let mut view = view((37, 23), (640.0, 480.0), 1.5);
+    view.zoom_at(300.0, 200.0, 2.0);
+    for &(x, y) in &[(0, 0), (36, 22), (10, 5)] {
+        let (rx, ry) = rendered_center(&view, x, y);
+        let (cx, cy) = view.cell_to_screen(x, y);
+        assert!((rx - cx).abs() < 1e-2 && (ry - cy).abs() < 1e-2);
+    }
+}
+
+#[test]
+fn row_zero_is_at_the_bottom() {
+    let view = View::new(10, 10, 100, 100);
+    assert_eq!(view.screen_to_cell(5.0, 99.0), (0, 0));
+    assert_eq!(view.screen_to_cell(5.0, 1.0), (0, 9));
+    assert_eq!(view.screen_to_cell(95.0, 1.0), (9, 9));
+}
+
+#[test]
+fn board_is_centered_with_square_cells() {
+    //窗口比棋盘宽时左右留黑边
+    let view = View::new(10, 10, 200, 100);
+    assert_eq!(view.cell_size(), 10.0);
+    assert_eq!(view.cell_to_screen(0, 9), (55.0, 5.0));
+    assert_eq!(view.screen_to_cell(10.0, 50.0).0, -4);
+
+    //窗口比棋盘高时上下留黑边
+    let view = View::new(20, 10, 100, 100);
+    assert_eq!(view.cell_to_screen(0, 0), (2.5, 72.5));
+    assert_eq!(view.screen_to_cell(50.0, 10.0).1, 13);
+}
+
+#[test]
+fn zoom_keeps_point_under_cursor() {
+    let mut view = View::new(64, 48, 800, 600);
+    let before = view.screen_to_board(123.0, 456.0);
+    view.zoom_at(123.0, 456.0, 2.5);
+    let after = view.screen_to_board(123.0, 456.0);
+    assert!((before.0 - after.0).abs() < 1e-9 && (before.1 - after.1).abs() < 1e-9);
+    assert_eq!(view.zoom(), 2.5);
+
+    //缩放倍数有上下限
+    view.zoom_at(0.0, 0.0, 1e-6);
+    assert_eq!(view.zoom(), MIN_ZOOM);
+    view.zoom_at(0.0, 0.0, 1e6);
+    assert_eq!(view.cell_size(), MAX_CELL_SIZE);
+}
+
+#[test]
+fn pan_moves_content_with_the_mouse() {
+    let mut view = View::new(10, 10, 100, 100);
+    let (x, y) = view.cell_to_screen(3, 3);
+    view.pan(20.0, -10.0);
+    assert_eq!(view.cell_to_screen(3, 3), (x + 20.0, y - 10.0));
+}
+
+#[test]
+fn fit_and_reset() {
+    let mut view = View::new(100, 100, 400, 400);
+    view.fit((10.0, 20.0), (30.0, 30.0));
+    assert_eq!(view.center(), (20.0, 25.0));
+    //20 个细胞宽的区域填满 400 像素
+    assert_eq!(view.cell_size(), 20.0);
+
+    view.reset();
+    assert_eq!(view.center(), (50.0, 50.0));
+    assert_eq!(view.zoom(), 1.0);
+    assert_eq!(view.cell_size(), 4.0);
+}